@@ -0,0 +1,41 @@
+//! Runtime companion of `derive_debug`: the `Inspect` trait, implemented by `#[derive(CustomDebug)]` on types with
+//! `#[debug(inspect)]`.
+//!
+//! `Inspect` walks the fields printed by `Debug` as `(name, value)` pairs, so the same type can be rendered in other
+//! shapes, like `key=value` log lines or JSON, without a second derive.
+
+#![no_std]
+
+use core::fmt::{self, Debug, Display, Formatter};
+
+/// A type whose fields can be visited one by one, as `Debug` prints them.
+///
+/// Fields are visited in declaration order, with the same options as `Debug`: skipped fields are not visited, renamed
+/// fields are visited under their new name, and values are printed with their custom format. Tuple fields are named
+/// by their index.
+pub trait Inspect {
+    /// Return the name of the struct, or of the variant of the enum.
+    fn name(&self) -> &'static str;
+
+    /// Call the visitor with the name and value of each field.
+    fn inspect(&self, visitor: &mut dyn FnMut(&str, &dyn Debug));
+}
+
+/// Display the fields of a value as `key=value` pairs separated by spaces, like `id=1 name="ferris"`.
+pub struct KeyValues<'a, T: ?Sized>(pub &'a T);
+
+impl<T: Inspect + ?Sized> Display for KeyValues<'_, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut result: fmt::Result = Ok(());
+        let mut separator: &str = "";
+
+        self.0.inspect(&mut |name: &str, value: &dyn Debug| {
+            if result.is_ok() {
+                result = write!(f, "{}{}={:?}", separator, name, value);
+                separator = " ";
+            }
+        });
+
+        result
+    }
+}
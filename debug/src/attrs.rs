@@ -0,0 +1,197 @@
+use syn::punctuated::Punctuated;
+use syn::token::Comma;
+use proc_macro2::{TokenStream, TokenTree};
+use quote::ToTokens;
+use syn::{Attribute, Error, Expr, ExprLit, Lit, LitInt, LitStr, Meta, MetaNameValue, Path, Type, WherePredicate};
+
+use crate::format::{check_field_format, placeholders, radix_format, spec_trait};
+
+/// Options of the struct or enum, from its #[debug(...)] attributes.
+#[derive(Default)]
+pub(crate) struct ContainerOptions {
+    pub(crate) bound: Option<Vec<WherePredicate>>,  // Replaces every inferred bound
+    pub(crate) non_exhaustive: bool,  // Ends the output of every shape with `..`
+    pub(crate) inspect: bool,  // Also implements `derive_debug_inspect::Inspect`
+}
+
+/// Options of a field, from its #[debug = "..."] and #[debug(...)] attributes.
+#[derive(Default)]
+pub(crate) struct FieldOptions {
+    pub(crate) format: ValueFormat,
+    pub(crate) rename: Option<LitStr>,
+    pub(crate) bound: Option<Vec<WherePredicate>>,  // Replaces the bounds inferred from the field
+}
+
+/// How the value of a field is printed.
+#[derive(Default)]
+pub(crate) enum ValueFormat {
+    #[default]
+    Debug,
+    Format(LitStr),  // #[debug = "..."], formatting the field
+    FormatArgs(LitStr, Vec<Expr>),  // #[debug("...", args)], formatting other expressions like `self.den`
+    Redact,  // Printed as <redacted>
+    With(Path),  // Function formatting the field, like `fn(&T, &mut Formatter) -> fmt::Result`
+    Elements(LitStr),  // Collection printed as a list, formatting each element, like `[0x0a, 0xff]`
+    Bytes,  // Byte collection printed as an escaped byte string, like `b"ab\x00"`
+    Limit(usize),  // Collection printed with at most this many elements, followed by `..`
+    LenOnly,  // Collection printed as its length, like `len=1234`
+    Skip,
+}
+
+impl ValueFormat {
+    /// Return the formatting trait the field type needs to implement, like `Debug`, if the field is formatted.
+    ///
+    /// A custom format string uses the trait of its placeholder, like `LowerHex` for `0x{:x}`.
+    pub(crate) fn field_trait(&self) -> Option<&'static str> {
+        match self {
+            ValueFormat::Debug | ValueFormat::Limit(_) => Some("Debug"),
+            ValueFormat::Format(format) => placeholders(format).ok()?.first().map(|x| spec_trait(&x.spec)),
+            _ => None,
+        }
+    }
+
+    /// Return true if the value of the field is printed in any way.
+    pub(crate) fn uses_value(&self) -> bool {
+        !matches!(self, ValueFormat::FormatArgs(..) | ValueFormat::Skip)
+    }
+}
+
+/// Options of the struct, enum or variant, from its #[display(...)] attributes.
+#[derive(Default)]
+pub(crate) struct DisplayOptions {
+    pub(crate) template: Option<LitStr>,  // #[display("...")], interpolating the fields by name
+    pub(crate) bound: Option<Vec<WherePredicate>>,  // Replaces every inferred bound, on the struct or enum only
+}
+
+impl ContainerOptions {
+    pub(crate) fn parse(attrs: &[Attribute]) -> Result<Self, Error> {
+        let mut options: ContainerOptions = ContainerOptions::default();
+
+        for attr in debug_attributes(attrs) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("bound") {
+                    options.bound = Some(parse_bound(&meta.value()?.parse()?)?);
+                } else if meta.path.is_ident("non_exhaustive") {
+                    options.non_exhaustive = true;
+                } else if meta.path.is_ident("inspect") {
+                    options.inspect = true;
+                } else {
+                    return Err(meta.error("unsupported #[debug] option"))
+                }
+
+                Ok(())
+            })?;
+        }
+
+        Ok(options)
+    }
+}
+
+impl FieldOptions {
+    /// Parse the options of a field, whose type gives the width of the hex, bin and octal shorthands.
+    pub(crate) fn parse(attrs: &[Attribute], ty: &Type) -> Result<Self, Error> {
+        let mut options: FieldOptions = FieldOptions::default();
+
+        for attr in debug_attributes(attrs) {
+            match &attr.meta {
+                Meta::NameValue(MetaNameValue { value: Expr::Lit(ExprLit { lit: Lit::Str(format), .. }), .. }) => {
+                    check_field_format(format)?;
+                    options.set_format(attr, ValueFormat::Format(format.clone()))?;
+                },
+                // Options start with an identifier, format strings with a literal
+                Meta::List(list) if matches!(list.tokens.clone().into_iter().next(), Some(TokenTree::Literal(_))) => {
+                    let args: Punctuated<Expr, Comma> = list.parse_args_with(Punctuated::parse_terminated)?;
+                    let mut args = args.into_iter();
+                    let format: LitStr = match args.next() {
+                        Some(Expr::Lit(ExprLit { lit: Lit::Str(format), .. })) => format,
+                        _ => return Err(Error::new_spanned(&list.tokens, "expected format string")),
+                    };
+                    options.set_format(attr, ValueFormat::FormatArgs(format, args.collect()))?;
+                },
+                Meta::NameValue(name_value) => {
+                    return Err(Error::new_spanned(&name_value.value, "expected format string"))
+                },
+                _ => attr.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("bound") {
+                        options.bound = Some(parse_bound(&meta.value()?.parse()?)?);
+                    } else if meta.path.is_ident("rename") {
+                        options.rename = Some(meta.value()?.parse()?);
+                    } else if meta.path.is_ident("skip") {
+                        options.set_format(&meta.path, ValueFormat::Skip)?;
+                    } else if meta.path.is_ident("redact") {
+                        options.set_format(&meta.path, ValueFormat::Redact)?;
+                    } else if meta.path.is_ident("with") {
+                        let path: LitStr = meta.value()?.parse()?;
+                        options.set_format(&meta.path, ValueFormat::With(path.parse()?))?;
+                    } else if meta.path.is_ident("limit") {
+                        let limit: LitInt = meta.value()?.parse()?;
+                        options.set_format(&meta.path, ValueFormat::Limit(limit.base10_parse()?))?;
+                    } else if meta.path.is_ident("hex") || meta.path.is_ident("bin") || meta.path.is_ident("octal") {
+                        options.set_format(&meta.path, radix_format(&meta.path, ty)?)?;
+                    } else if meta.path.is_ident("bytes") {
+                        options.set_format(&meta.path, ValueFormat::Bytes)?;
+                    } else if meta.path.is_ident("len_only") {
+                        options.set_format(&meta.path, ValueFormat::LenOnly)?;
+                    } else {
+                        return Err(meta.error("unsupported #[debug] option"))
+                    }
+
+                    Ok(())
+                })?,
+            }
+        }
+
+        Ok(options)
+    }
+}
+
+impl DisplayOptions {
+    pub(crate) fn parse(attrs: &[Attribute]) -> Result<Self, Error> {
+        let mut options: DisplayOptions = DisplayOptions::default();
+
+        for attr in attrs.iter().filter(|x| x.path().is_ident("display")) {
+            match &attr.meta {
+                // Options start with an identifier, templates with a literal
+                Meta::List(list) if matches!(list.tokens.clone().into_iter().next(), Some(TokenTree::Literal(_))) => {
+                    if options.template.is_some() {
+                        return Err(Error::new_spanned(attr, "duplicate #[display] template"))
+                    }
+                    options.template = Some(list.parse_args()?);
+                },
+                _ => attr.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("bound") {
+                        options.bound = Some(parse_bound(&meta.value()?.parse()?)?);
+                        Ok(())
+                    } else {
+                        Err(meta.error("unsupported #[display] option"))
+                    }
+                })?,
+            }
+        }
+
+        Ok(options)
+    }
+}
+
+impl FieldOptions {
+    /// Set how the value is printed, which can only be done once.
+    fn set_format(&mut self, tokens: impl ToTokens, format: ValueFormat) -> Result<(), Error> {
+        if !matches!(self.format, ValueFormat::Debug) {
+            let tokens: TokenStream = tokens.into_token_stream();
+            return Err(Error::new_spanned(tokens, "conflicting #[debug] options, a field is printed in a single way"))
+        }
+
+        self.format = format;
+        Ok(())
+    }
+}
+
+fn debug_attributes(attrs: &[Attribute]) -> impl Iterator<Item = &Attribute> {
+    attrs.iter().filter(|x| x.path().is_ident("debug"))
+}
+
+/// Parse the where predicates of a `bound = "..."` option, which may be empty.
+fn parse_bound(bound: &LitStr) -> Result<Vec<WherePredicate>, Error> {
+    let predicates: Punctuated<WherePredicate, Comma> = bound.parse_with(Punctuated::parse_terminated)?;
+    Ok(predicates.into_iter().collect())
+}
@@ -0,0 +1,111 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{parse_quote, GenericArgument, GenericParam, Generics, Ident, PathArguments, Type, WhereClause, WherePredicate};
+
+/// Return the identifiers of the type parameters.
+pub(crate) fn type_params(generics: &Generics) -> Vec<&Ident> {
+    generics.params
+        .iter()
+        .filter_map(|x| match x {
+            GenericParam::Type(param) => Some(&param.ident),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Build the bounds required to format a field with the trait, like `::core::fmt::Debug`.
+pub(crate) fn inferred_bounds(ty: &Type, generics: &Generics, format_trait: &TokenStream) -> Vec<WherePredicate> {
+    let params: Vec<&Ident> = type_params(generics);
+
+    used_types(ty, &params)
+        .into_iter()
+        .map(|ty| -> WherePredicate { parse_quote! { #ty: #format_trait } })
+        .collect()
+}
+
+/// Return the generics with the predicates added to their where clause, skipping duplicates.
+pub(crate) fn with_predicates(generics: &Generics, predicates: Vec<WherePredicate>) -> Generics {
+    let mut generics: Generics = generics.clone();
+    let where_clause: &mut WhereClause = generics.make_where_clause();
+
+    for predicate in predicates {
+        if !where_clause.predicates.iter().any(|x| *x == predicate) {
+            where_clause.predicates.push(predicate);
+        }
+    }
+
+    generics
+}
+
+/// Return the path of a formatting trait of `core::fmt`, from its name.
+pub(crate) fn fmt_trait(name: &str) -> TokenStream {
+    let ident: Ident = Ident::new(name, proc_macro2::Span::call_site());
+    quote! { ::core::fmt::#ident }
+}
+
+/// Return the types a field needs to implement a formatting trait for the field to implement it.
+///
+/// Those are the type parameters used in the field type, found through references, arrays, slices, tuples and
+/// the arguments of generic types like `Vec<Option<T>>`. If the field type uses an associated type of a type
+/// parameter, like `Vec<T::Value>`, the whole field type is bounded instead.
+///
+/// Type parameters only used in a `PhantomData`, or in a pointer or function type, do not need to implement it.
+pub(crate) fn used_types(ty: &Type, params: &[&Ident]) -> Vec<Type> {
+    let mut types: Vec<Type> = Vec::new();
+    let mut projection: bool = false;
+    collect_used_types(ty, params, &mut types, &mut projection);
+
+    if projection { vec![ty.clone()] } else { types }
+}
+
+fn collect_used_types(ty: &Type, params: &[&Ident], types: &mut Vec<Type>, projection: &mut bool) {
+    match ty {
+        // A qualified associated type like `<T as Trait>::Value`, if it depends on a type parameter
+        Type::Path(type_path) if type_path.qself.is_some() => {
+            let mut qself_types: Vec<Type> = Vec::new();
+            collect_used_types(&type_path.qself.as_ref().unwrap().ty, params, &mut qself_types, projection);
+            *projection |= !qself_types.is_empty();
+        },
+        Type::Path(type_path) => {
+            let segments = &type_path.path.segments;
+            let first: &Ident = &segments[0].ident;
+
+            if segments.last().unwrap().ident == "PhantomData" {
+                return
+            }
+
+            // `T` itself, or an associated type of it like `T::Value`
+            if params.contains(&first) {
+                if segments.len() > 1 {
+                    *projection = true;
+                } else if !types.contains(ty) {
+                    types.push(ty.clone());
+                }
+                return
+            }
+
+            // Type parameters used as arguments, like in `Vec<T>`
+            for segment in segments {
+                if let PathArguments::AngleBracketed(arguments) = &segment.arguments {
+                    for argument in &arguments.args {
+                        if let GenericArgument::Type(ty) = argument {
+                            collect_used_types(ty, params, types, projection);
+                        }
+                    }
+                }
+            }
+        },
+        Type::Array(array) => collect_used_types(&array.elem, params, types, projection),
+        Type::Group(group) => collect_used_types(&group.elem, params, types, projection),
+        Type::Paren(paren) => collect_used_types(&paren.elem, params, types, projection),
+        Type::Reference(reference) => collect_used_types(&reference.elem, params, types, projection),
+        Type::Slice(slice) => collect_used_types(&slice.elem, params, types, projection),
+        Type::Tuple(tuple) => {
+            for elem in &tuple.elems {
+                collect_used_types(elem, params, types, projection);
+            }
+        },
+        // Pointers and function pointers are printed as addresses, trait objects can not be inspected
+        _ => {},
+    }
+}
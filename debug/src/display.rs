@@ -0,0 +1,126 @@
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote, ToTokens};
+use syn::ext::IdentExt;
+use syn::{Data, DeriveInput, Error, Fields, Generics, Ident, LitStr, WherePredicate};
+
+use crate::attrs::DisplayOptions;
+use crate::bounds::{fmt_trait, inferred_bounds, with_predicates};
+use crate::format::{placeholders, rewrite_placeholders, spec_trait, Placeholder};
+
+/// The expansion of `#[derive(CustomDisplay)]`.
+pub(crate) fn derive_display(input_derive: DeriveInput) -> Result<TokenStream, Error> {
+    let name: &Ident = &input_derive.ident;
+    let options: DisplayOptions = DisplayOptions::parse(&input_derive.attrs)?;
+    let mut predicates: Vec<WherePredicate> = Vec::new();
+
+    // Structs have their template on the struct, enums on each variant
+    let arms: Vec<TokenStream> = match &input_derive.data {
+        Data::Struct(data) => {
+            let template: &LitStr = options.template.as_ref().ok_or_else(|| missing_template(name))?;
+            vec![template_arm(template, quote! { Self }, &data.fields, &input_derive.generics, &mut predicates)?]
+        },
+        Data::Enum(data) => {
+            if let Some(template) = &options.template {
+                return Err(Error::new_spanned(template, "the template of an enum goes on each variant"))
+            }
+
+            data.variants
+                .iter()
+                .map(|variant| {
+                    let variant_options: DisplayOptions = DisplayOptions::parse(&variant.attrs)?;
+                    if let Some(bound) = &variant_options.bound {
+                        return Err(Error::new_spanned(&bound[0], "bound is only supported on the enum"))
+                    }
+                    let template: &LitStr = variant_options.template.as_ref().ok_or_else(|| missing_template(&variant.ident))?;
+                    let variant_ident: &Ident = &variant.ident;
+                    template_arm(template, quote! { Self::#variant_ident }, &variant.fields, &input_derive.generics, &mut predicates)
+                })
+                .collect::<Result<_, Error>>()?
+        },
+        Data::Union(_) => return Err(Error::new_spanned(name, "CustomDisplay does not support unions")),
+    };
+
+    // The bound of the attribute replaces the inferred ones
+    if let Some(bound) = options.bound {
+        predicates = bound;
+    }
+
+    let generics: Generics = with_predicates(&input_derive.generics, predicates);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::core::fmt::Display for #name #ty_generics #where_clause {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                match *self {
+                    #( #arms )*
+                }
+            }
+        }
+    })
+}
+
+fn missing_template(ident: &Ident) -> Error {
+    Error::new_spanned(ident, format!("missing #[display(\"...\")] template on {}", ident))
+}
+
+/// Build the match arm writing the template of a struct or variant, binding only the fields it refers to.
+///
+/// Adds the bounds the referenced fields need, for the trait of each placeholder, to the predicates.
+fn template_arm(
+    template: &LitStr,
+    path: TokenStream,
+    fields: &Fields,
+    generics: &Generics,
+    predicates: &mut Vec<WherePredicate>,
+) -> Result<TokenStream, Error> {
+    // Fields are referred to by name, or by index for tuple fields
+    let names: Vec<String> = fields
+        .iter()
+        .enumerate()
+        .map(|(index, field)| match &field.ident {
+            Some(ident) => ident.unraw().to_string(),
+            None => index.to_string(),
+        })
+        .collect();
+    let bindings: Vec<Ident> = (0..names.len())
+        .map(|index| format_ident!("__field_{}", index, span = Span::call_site()))
+        .collect();
+
+    let mut used: Vec<bool> = vec![false; names.len()];
+    for Placeholder { argument, spec } in placeholders(template)? {
+        if argument.is_empty() {
+            return Err(Error::new_spanned(template, "placeholders of #[display] templates must name a field, like {name}"))
+        }
+        if spec.contains(['$', '*']) {
+            return Err(Error::new_spanned(template, "width and precision arguments are not supported in #[display] templates"))
+        }
+
+        let Some(index) = names.iter().position(|x| *x == argument) else {
+            return Err(Error::new_spanned(template, format!("no field `{}` to interpolate", argument)))
+        };
+        used[index] = true;
+        predicates.extend(inferred_bounds(&fields.iter().nth(index).unwrap().ty, generics, &fmt_trait(spec_trait(&spec))));
+    }
+
+    // Placeholders refer to the bindings, passed as named arguments
+    let format: LitStr = rewrite_placeholders(template, |argument, spec| {
+        let index: usize = names.iter().position(|x| x == argument)?;
+        Some(if spec.is_empty() { format!("{}", bindings[index]) } else { format!("{}:{}", bindings[index], spec) })
+    }).unwrap_or_else(|| template.clone());
+
+    let patterns = fields.iter().zip(&bindings).zip(&used).map(|((_, binding), used)| {
+        if *used { quote! { ref #binding } } else { quote! { _ } }
+    });
+    let args = bindings.iter().zip(&used).filter(|(_, used)| **used).map(|(binding, _)| quote! { #binding = #binding });
+
+    let pattern: TokenStream = match fields {
+        Fields::Named(_) => {
+            let idents = fields.iter().map(|field| field.ident.to_token_stream());
+            quote! { #path { #( #idents: #patterns ),* } }
+        },
+        Fields::Unnamed(_) => quote! { #path( #( #patterns ),* ) },
+        Fields::Unit => quote! { #path },
+    };
+
+    Ok(quote! { #pattern => ::core::write!(f, #format #(, #args )*), })
+}
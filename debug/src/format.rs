@@ -0,0 +1,204 @@
+use syn::{Error, GenericArgument, LitStr, Path, PathArguments, Type};
+
+use crate::attrs::ValueFormat;
+
+/// A `{...}` placeholder of a format string.
+pub(crate) struct Placeholder {
+    pub(crate) argument: String,  // Before the colon, empty for the next positional argument
+    pub(crate) spec: String,  // After the colon
+}
+
+/// Return the placeholders of a format string, skipping escaped braces.
+pub(crate) fn placeholders(format: &LitStr) -> Result<Vec<Placeholder>, Error> {
+    let value: String = format.value();
+    let mut chars = value.chars().peekable();
+    let mut placeholders: Vec<Placeholder> = Vec::new();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => { chars.next(); },
+            '}' if chars.peek() == Some(&'}') => { chars.next(); },
+            '{' => {
+                let content: String = chars.by_ref().take_while(|x| *x != '}').collect();
+                let (argument, spec) = content.split_once(':').unwrap_or((&content, ""));
+                placeholders.push(Placeholder { argument: argument.trim().to_string(), spec: spec.to_string() });
+            },
+            '}' => return Err(Error::new_spanned(format, "invalid format string: unmatched `}`")),
+            _ => {},
+        }
+    }
+
+    Ok(placeholders)
+}
+
+/// Check that the format string of a field has exactly one placeholder, which is the field.
+pub(crate) fn check_field_format(format: &LitStr) -> Result<(), Error> {
+    let placeholders: Vec<Placeholder> = placeholders(format)?;
+
+    match placeholders.as_slice() {
+        [placeholder] if matches!(placeholder.argument.as_str(), "" | "0") && !placeholder.spec.contains(['$', '*']) => Ok(()),
+        [_] => Err(Error::new_spanned(format, "the placeholder of a field format string can only refer to the field")),
+        _ => Err(Error::new_spanned(format, format!(
+            "expected exactly one placeholder in the field format string, found {}", placeholders.len()
+        ))),
+    }
+}
+
+/// Return the format string with the `#` flag added to its `Debug` placeholders, if any.
+///
+/// Used to pretty-print the values of a custom format under `{:#?}`.
+pub(crate) fn alternate_format(format: &LitStr) -> Option<LitStr> {
+    rewrite_placeholders(format, |argument, spec| {
+        if !spec.ends_with('?') || spec.contains('#') {
+            return None
+        }
+
+        // The flag goes after the fill, alignment and sign
+        let chars: Vec<(usize, char)> = spec.char_indices().collect();
+        let mut flag_index: usize = 0;
+        if chars.len() > 1 && matches!(chars[1].1, '<' | '^' | '>') {
+            flag_index = chars[2].0;
+        } else if matches!(chars[0].1, '<' | '^' | '>') {
+            flag_index = chars[1].0;
+        }
+        if spec[flag_index..].starts_with(['+', '-']) {
+            flag_index += 1;
+        }
+
+        Some(format!("{}:{}#{}", argument, &spec[..flag_index], &spec[flag_index..]))
+    })
+}
+
+/// Return the name of the formatting trait used by a placeholder, from the type at the end of its spec.
+pub(crate) fn spec_trait(spec: &str) -> &'static str {
+    match spec.chars().last() {
+        Some('?') => "Debug",
+        Some('x') => "LowerHex",
+        Some('X') => "UpperHex",
+        Some('o') => "Octal",
+        Some('b') => "Binary",
+        Some('e') => "LowerExp",
+        Some('E') => "UpperExp",
+        Some('p') => "Pointer",
+        _ => "Display",
+    }
+}
+
+/// Rewrite the placeholders of a format string, given their argument and spec.
+///
+/// The closure returns the new content of the placeholder, or None to keep it.
+/// Return None if no placeholder was rewritten.
+pub(crate) fn rewrite_placeholders<F>(format: &LitStr, mut rewrite: F) -> Option<LitStr>
+where
+    F: FnMut(&str, &str) -> Option<String>,
+{
+    let value: String = format.value();
+    let mut result: String = String::new();
+    let mut changed: bool = false;
+    let mut rest: &str = &value;
+
+    while let Some(start) = rest.find(['{', '}']) {
+        result.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        // Escaped braces are copied as is
+        if !rest.starts_with('{') || rest.starts_with("{{") {
+            let len: usize = if rest.starts_with("{{") || rest.starts_with("}}") { 2 } else { 1 };
+            result.push_str(&rest[..len]);
+            rest = &rest[len..];
+            continue
+        }
+
+        let end: usize = rest.find('}').map_or(rest.len(), |x| x + 1);
+        let placeholder: &str = &rest[..end];
+        rest = &rest[end..];
+
+        let content: &str = placeholder.trim_start_matches('{').trim_end_matches('}');
+        let (argument, spec) = content.split_once(':').unwrap_or((content, ""));
+        match rewrite(argument.trim(), spec) {
+            Some(content) => {
+                result.push_str(&format!("{{{}}}", content));
+                changed = true;
+            },
+            None => result.push_str(placeholder),
+        }
+    }
+    result.push_str(rest);
+
+    changed.then(|| LitStr::new(&result, format.span()))
+}
+
+/// Return the format of a `hex`, `bin` or `octal` option, padded to the width of the integer type of the field.
+///
+/// Integers are printed with a prefix, like `0x0a` for a `u8`, and collections of integers as a list of those.
+pub(crate) fn radix_format(option: &Path, ty: &Type) -> Result<ValueFormat, Error> {
+    let (kind, bits_per_digit): (char, u32) = match option.get_ident().map(|x| x.to_string()).as_deref() {
+        Some("hex") => ('x', 4),
+        Some("bin") => ('b', 1),
+        _ => ('o', 3),
+    };
+    let format = |bits: Option<u32>| -> LitStr {
+        let value: String = match bits {
+            Some(bits) => format!("{{:#0{}{}}}", bits.div_ceil(bits_per_digit) + 2, kind),
+            None => format!("{{:#{}}}", kind),
+        };
+        LitStr::new(&value, option.get_ident().unwrap().span())
+    };
+
+    let ty: &Type = strip_references(ty);
+    if let Some(bits) = integer_bits(ty) {
+        return Ok(ValueFormat::Format(format(bits)))
+    }
+    if let Some(bits) = element_type(ty).and_then(integer_bits) {
+        return Ok(ValueFormat::Elements(format(bits)))
+    }
+
+    Err(Error::new_spanned(option, format!(
+        "{} is only supported on integers and collections of integers", option.get_ident().unwrap()
+    )))
+}
+
+/// Return the size in bits of a primitive integer type, None for `usize` and `isize` whose size depends on the target.
+fn integer_bits(ty: &Type) -> Option<Option<u32>> {
+    let Type::Path(type_path) = ty else { return None };
+    let ident: String = type_path.path.get_ident()?.to_string();
+
+    match ident.as_str() {
+        "u8" | "i8" => Some(Some(8)),
+        "u16" | "i16" => Some(Some(16)),
+        "u32" | "i32" => Some(Some(32)),
+        "u64" | "i64" => Some(Some(64)),
+        "u128" | "i128" => Some(Some(128)),
+        "usize" | "isize" => Some(None),
+        _ => None,
+    }
+}
+
+/// Return the element type of an array, a slice, or a collection like `Vec<T>`, `VecDeque<T>` or `Box<[T]>`.
+fn element_type(ty: &Type) -> Option<&Type> {
+    match ty {
+        Type::Array(array) => Some(strip_references(&array.elem)),
+        Type::Slice(slice) => Some(strip_references(&slice.elem)),
+        Type::Path(type_path) => {
+            let segment = type_path.path.segments.last()?;
+            let PathArguments::AngleBracketed(arguments) = &segment.arguments else { return None };
+            let Some(GenericArgument::Type(elem)) = arguments.args.first() else { return None };
+
+            match segment.ident.to_string().as_str() {
+                "Vec" | "VecDeque" => Some(strip_references(elem)),
+                "Box" => element_type(strip_references(elem)),
+                _ => None,
+            }
+        },
+        _ => None,
+    }
+}
+
+fn strip_references(ty: &Type) -> &Type {
+    match ty {
+        Type::Reference(reference) => strip_references(&reference.elem),
+        Type::Group(group) => strip_references(&group.elem),
+        Type::Paren(paren) => strip_references(&paren.elem),
+        _ => ty,
+    }
+}
@@ -1,8 +1,314 @@
+mod attrs;
+mod bounds;
+mod display;
+mod format;
+
 use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+
+use quote::{format_ident, quote};
+use syn::{parse_macro_input, Data, DeriveInput, Error, Fields, Generics, Ident, LitStr, Type};
+use syn::ext::IdentExt;
+use syn::WherePredicate;
+use crate::attrs::{ContainerOptions, FieldOptions, ValueFormat};
+use crate::bounds::{fmt_trait, inferred_bounds, with_predicates};
+use crate::format::alternate_format;
 
-#[proc_macro_derive(CustomDebug)]
+#[proc_macro_derive(CustomDebug, attributes(debug))]
 pub fn derive(input: TokenStream) -> TokenStream {
-    let _ = input;
+    // Parse the input tokens into a syntax tree
+    let input_derive: DeriveInput = parse_macro_input!(input);
+
+    // Hand the output tokens back to the compiler
+    match _derive(input_derive) {
+        Ok(result) => result.into(),
+        Err(error) => error.to_compile_error().into(),
+    }
+}
+
+#[proc_macro_derive(CustomDisplay, attributes(display))]
+pub fn derive_display(input: TokenStream) -> TokenStream {
+    let input_derive: DeriveInput = parse_macro_input!(input);
+
+    match display::derive_display(input_derive) {
+        Ok(result) => result.into(),
+        Err(error) => error.to_compile_error().into(),
+    }
+}
+
+/// A field of the struct or of a variant, bound by a pattern on `self`.
+struct Field<'a> {
+    ident: Option<&'a Ident>,  // None for tuple fields
+    ty: &'a Type,
+    options: FieldOptions,
+    binding: Ident,
+}
+
+/// A struct or an enum variant: its name, pattern and fields.
+struct Shape<'a> {
+    name: &'a Ident,
+    path: TokenStream2,  // `Self` or `Self::Variant`
+    style: Style,
+    fields: Vec<Field<'a>>,
+}
+
+#[derive(Clone, Copy)]
+enum Style {
+    Named,
+    Tuple,
+    Unit,
+}
+
+
+fn _derive(input_derive: DeriveInput) -> Result<TokenStream2, Error> {
+    let name: &Ident = &input_derive.ident;
+    let container_options: ContainerOptions = ContainerOptions::parse(&input_derive.attrs)?;
+
+    // Structs are a single shape, enums have one per variant
+    let shapes: Vec<Shape> = match &input_derive.data {
+        Data::Struct(data) => vec![shape(name, quote! { Self }, &data.fields)?],
+        Data::Enum(data) => data.variants
+            .iter()
+            .map(|variant| {
+                let variant_ident: &Ident = &variant.ident;
+                shape(variant_ident, quote! { Self::#variant_ident }, &variant.fields)
+            })
+            .collect::<Result<_, Error>>()?,
+        Data::Union(_) => return Err(Error::new_spanned(name, "CustomDebug does not support unions")),
+    };
+
+    let arms = shapes.iter().map(|shape| {
+        let pattern: TokenStream2 = shape_pattern(shape);
+        let body: TokenStream2 = shape_body(shape, container_options.non_exhaustive);
+        quote! { #pattern => #body, }
+    });
+
+    // The bounds of the attribute replace the inferred ones, for the whole type or for a single field
+    let predicates: Vec<WherePredicate> = match &container_options.bound {
+        Some(bound) => bound.clone(),
+        None => shapes
+            .iter()
+            .flat_map(|shape| &shape.fields)
+            .flat_map(|field| match &field.options.bound {
+                Some(bound) => bound.clone(),
+                None => match field.options.format.field_trait() {
+                    Some(field_trait) => inferred_bounds(field.ty, &input_derive.generics, &fmt_trait(field_trait)),
+                    None => Vec::new(),
+                },
+            })
+            .collect(),
+    };
+
+    let generics: Generics = with_predicates(&input_derive.generics, predicates);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    // Fields with a custom format are printed through a closure, wrapped to implement Debug
+    let uses_closure: bool = shapes.iter().flat_map(|shape| &shape.fields).any(|x| match &x.options.format {
+        ValueFormat::Format(format) | ValueFormat::FormatArgs(format, _) => alternate_format(format).is_some(),
+        ValueFormat::With(_) | ValueFormat::Elements(_) | ValueFormat::Limit(_) => true,
+        _ => false,
+    });
+    let debug_fn: Option<TokenStream2> = uses_closure.then(|| quote! {
+        struct __DebugFn<F: ::core::ops::Fn(&mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result>(F);
+
+        impl<F: ::core::ops::Fn(&mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result> ::core::fmt::Debug for __DebugFn<F> {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                (self.0)(f)
+            }
+        }
+    });
+
+    // The fields printed by Debug can also be walked one by one, with the same options
+    let inspect_impl: Option<TokenStream2> = container_options.inspect.then(|| {
+        let name_arms = shapes.iter().map(|shape| {
+            let path: &TokenStream2 = &shape.path;
+            let name: String = shape.name.unraw().to_string();
+            match shape.style {
+                Style::Named => quote! { #path { .. } => #name, },
+                Style::Tuple => quote! { #path(..) => #name, },
+                Style::Unit => quote! { #path => #name, },
+            }
+        });
+        let inspect_arms = shapes.iter().map(|shape| {
+            let pattern: TokenStream2 = shape_pattern(shape);
+            let visits: TokenStream2 = shape_inspect(shape);
+            quote! { #pattern => { #visits } }
+        });
+
+        quote! {
+            impl #impl_generics ::derive_debug_inspect::Inspect for #name #ty_generics #where_clause {
+                fn name(&self) -> &'static str {
+                    match *self {
+                        #( #name_arms )*
+                    }
+                }
+
+                fn inspect(&self, visitor: &mut dyn ::core::ops::FnMut(&str, &dyn ::core::fmt::Debug)) {
+                    #debug_fn
+
+                    match *self {
+                        #( #inspect_arms )*
+                    }
+                }
+            }
+        }
+    });
+
+    Ok(quote! {
+        impl #impl_generics ::core::fmt::Debug for #name #ty_generics #where_clause {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                #debug_fn
+
+                match *self {
+                    #( #arms )*
+                }
+            }
+        }
+
+        #inspect_impl
+    })
+}
+
+/// Build the shape of a struct or variant, parsing the options of its fields.
+fn shape<'a>(name: &'a Ident, path: TokenStream2, fields: &'a Fields) -> Result<Shape<'a>, Error> {
+    let style: Style = match fields {
+        Fields::Named(_) => Style::Named,
+        Fields::Unnamed(_) => Style::Tuple,
+        Fields::Unit => Style::Unit,
+    };
+
+    let fields: Vec<Field> = fields
+        .iter()
+        .enumerate()
+        .map(|(index, field)| {
+            let options: FieldOptions = FieldOptions::parse(&field.attrs, &field.ty)?;
+            if let (None, Some(rename)) = (&field.ident, &options.rename) {
+                return Err(Error::new_spanned(rename, "rename is only supported on named fields"))
+            }
+
+            Ok(Field {
+                ident: field.ident.as_ref(),
+                ty: &field.ty,
+                options,
+                binding: format_ident!("__field_{}", index, span = Span::call_site()),
+            })
+        })
+        .collect::<Result<_, Error>>()?;
+
+    Ok(Shape { name, path, style, fields })
+}
+
+/// Build the pattern binding every field of the shape by reference.
+fn shape_pattern(shape: &Shape) -> TokenStream2 {
+    let path: &TokenStream2 = &shape.path;
+    let bindings = shape.fields.iter().map(|field| {
+        let binding: &Ident = &field.binding;
+        if field.options.format.uses_value() { quote! { ref #binding } } else { quote! { _ } }
+    });
+
+    match shape.style {
+        Style::Named => {
+            let idents = shape.fields.iter().map(|field| field.ident);
+            quote! { #path { #( #idents: #bindings ),* } }
+        },
+        Style::Tuple => quote! { #path( #( #bindings ),* ) },
+        Style::Unit => quote! { #path },
+    }
+}
+
+/// Build the expression printing the shape, like `#[derive(Debug)]` does.
+///
+/// A non exhaustive shape ends with `..`, even if it has no fields.
+fn shape_body(shape: &Shape, non_exhaustive: bool) -> TokenStream2 {
+    let name: String = shape.name.unraw().to_string();
+    let fields: Vec<&Field> = shape.fields.iter().filter(|x| !matches!(x.options.format, ValueFormat::Skip)).collect();
+    let values = fields.iter().map(|field| field_value(field));
+
+    let finish: TokenStream2 = if non_exhaustive { quote! { finish_non_exhaustive } } else { quote! { finish } };
+    match shape.style {
+        Style::Named => {
+            let names = fields.iter().map(|field| field_name(field, 0));
+            quote! { f.debug_struct(#name) #( .field(#names, #values) )* .#finish() }
+        },
+        Style::Tuple => quote! { f.debug_tuple(#name) #( .field(#values) )* .#finish() },
+        Style::Unit if non_exhaustive => quote! { f.debug_struct(#name).finish_non_exhaustive() },
+        Style::Unit => quote! { f.write_str(#name) },
+    }
+}
+
+/// Build the statements calling the visitor of `Inspect::inspect` with each field printed by the shape.
+fn shape_inspect(shape: &Shape) -> TokenStream2 {
+    let visits = shape.fields
+        .iter()
+        .enumerate()
+        .filter(|(_, field)| !matches!(field.options.format, ValueFormat::Skip))
+        .map(|(index, field)| {
+            let name: String = field_name(field, index);
+            let value: TokenStream2 = field_value(field);
+            quote! { visitor(#name, #value); }
+        });
+
+    quote! { #( #visits )* }
+}
+
+/// Return the printed name of a field: its name or rename, or its index for tuple fields.
+fn field_name(field: &Field, index: usize) -> String {
+    match (&field.options.rename, field.ident) {
+        (Some(rename), _) => rename.value(),
+        (None, Some(ident)) => ident.unraw().to_string(),
+        (None, None) => index.to_string(),
+    }
+}
+
+/// Build the value printed for a field, a reference to something implementing `Debug`.
+fn field_value(field: &Field) -> TokenStream2 {
+    let binding: &Ident = &field.binding;
+    match &field.options.format {
+        ValueFormat::Debug => quote! { #binding },
+        ValueFormat::Format(format) => format_value(format, &quote! { #binding }),
+        ValueFormat::FormatArgs(format, args) => format_value(format, &quote! { #( #args ),* }),
+        ValueFormat::Redact => quote! { &::core::format_args!("<redacted>") },
+        ValueFormat::With(path) => quote! { &__DebugFn(|f: &mut ::core::fmt::Formatter<'_>| #path(#binding, f)) },
+        ValueFormat::Elements(format) => quote! {
+            &__DebugFn(|f: &mut ::core::fmt::Formatter<'_>| {
+                let mut list: ::core::fmt::DebugList<'_, '_> = f.debug_list();
+                for element in #binding.iter() {
+                    list.entry(&::core::format_args!(#format, element));
+                }
+                list.finish()
+            })
+        },
+        ValueFormat::Bytes => quote! { &::core::format_args!("b\"{}\"", #binding.escape_ascii()) },
+        ValueFormat::Limit(limit) => quote! {
+            &__DebugFn(|f: &mut ::core::fmt::Formatter<'_>| {
+                let mut iter = #binding.iter();
+                let mut list: ::core::fmt::DebugList<'_, '_> = f.debug_list();
+                list.entries(::core::iter::Iterator::take(::core::iter::Iterator::by_ref(&mut iter), #limit));
+                match ::core::iter::Iterator::next(&mut iter) {
+                    ::core::option::Option::Some(_) => list.finish_non_exhaustive(),
+                    ::core::option::Option::None => list.finish(),
+                }
+            })
+        },
+        ValueFormat::LenOnly => quote! { &::core::format_args!("len={}", #binding.len()) },
+        ValueFormat::Skip => unreachable!(),
+    }
+}
 
-    unimplemented!()
+/// Build the value printing the arguments with a custom format string.
+///
+/// The arguments are formatted lazily by `format_args!` or `write!`, without allocating.
+///
+/// Under `{:#?}`, the `Debug` placeholders of the format are pretty-printed too, indented by the formatter.
+fn format_value(format: &LitStr, args: &TokenStream2) -> TokenStream2 {
+    match alternate_format(format) {
+        None => quote! { &::core::format_args!(#format, #args) },
+        Some(alternate) => quote! {
+            &__DebugFn(|f: &mut ::core::fmt::Formatter<'_>| if f.alternate() {
+                ::core::write!(f, #alternate, #args)
+            } else {
+                ::core::write!(f, #format, #args)
+            })
+        },
+    }
 }
@@ -0,0 +1,85 @@
+// A `debug(bound = "...")` attribute on a field substitutes only the bounds
+// inferred from that field's type, the bounds inferred from the other fields
+// are kept.
+//
+//     impl<T: Trait, U> Debug for Wrapper<T, U>
+//     where
+//         T::Value: Debug,
+//         U: Debug,
+//     {...}
+//
+// The bound inferred for a field with a custom format uses the trait of its
+// placeholder, `T: LowerHex` for `0x{:x}` and `U: Display` for `<{}>`, rather
+// than `Debug`, which the field type does not need.
+//
+//     impl<T, U> Debug for Register<T, U>
+//     where
+//         T: LowerHex,
+//         U: Display,
+//     {...}
+
+use derive_debug::CustomDebug;
+use std::fmt::{self, Debug, Display, LowerHex};
+
+pub trait Trait {
+    type Value;
+}
+
+#[derive(CustomDebug)]
+pub struct Wrapper<T: Trait, U> {
+    #[debug(bound = "T::Value: Debug")]
+    field: Field<T>,
+    normal: U,
+}
+
+#[derive(CustomDebug)]
+struct Field<T: Trait> {
+    values: Vec<T::Value>,
+}
+
+#[derive(CustomDebug)]
+pub struct Register<T, U> {
+    #[debug = "0x{:x}"]
+    value: T,
+    #[debug = "<{}>"]
+    label: U,
+}
+
+// Implements LowerHex, but not Debug.
+pub struct Code(u8);
+
+impl LowerHex for Code {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        LowerHex::fmt(&self.0, f)
+    }
+}
+
+// Implements Display, but not Debug.
+pub struct Name(&'static str);
+
+impl Display for Name {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.0)
+    }
+}
+
+fn assert_debug<F: Debug>() {}
+
+fn main() {
+    struct Id;
+
+    impl Trait for Id {
+        type Value = u8;
+    }
+
+    assert_debug::<Wrapper<Id, bool>>();
+
+    let wrapper = Wrapper::<Id, bool> { field: Field { values: vec![1, 2] }, normal: true };
+    let debug = format!("{:?}", wrapper);
+    assert_eq!(debug, "Wrapper { field: Field { values: [1, 2] }, normal: true }");
+
+    assert_debug::<Register<Code, Name>>();
+
+    let register = Register { value: Code(255), label: Name("status") };
+    assert_eq!(format!("{:?}", register), "Register { value: 0xff, label: <status> }");
+}
@@ -0,0 +1,58 @@
+// Enums, tuple structs and unit structs print the same as with the standard
+// #[derive(Debug)], so that switching derives does not change the output.
+// Every variant is printed like the struct of the same shape.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+pub struct Meters(f64, #[debug = "{:.1}"] f64);
+
+#[derive(CustomDebug)]
+pub struct Marker;
+
+#[derive(CustomDebug)]
+pub enum Event<T> {
+    Click { x: i32, #[debug = "0x{:02x}"] button: u8 },
+    Key(char, T),
+    Close,
+}
+
+#[derive(CustomDebug)]
+pub enum Never {}
+
+mod std_derive {
+    #[derive(Debug)]
+    pub struct Meters(pub f64, pub f64);
+
+    #[derive(Debug)]
+    pub struct Marker;
+
+    #[derive(Debug)]
+    pub enum Event<T> {
+        Click { x: i32, button: u8 },
+        Key(char, T),
+        Close,
+    }
+}
+
+fn main() {
+    assert_eq!(format!("{:?}", Meters(1.0, 2.25)), "Meters(1.0, 2.2)");
+    assert_eq!(format!("{:?}", Marker), format!("{:?}", std_derive::Marker));
+
+    let events = [
+        (Event::Click { x: 3, button: 10 }, std_derive::Event::Click { x: 3, button: 10 }),
+        (Event::Key('a', "shift"), std_derive::Event::Key('a', "shift")),
+        (Event::Close, std_derive::Event::Close),
+    ];
+    for (event, std_event) in &events {
+        if let Event::Click { .. } = event {
+            assert_eq!(format!("{:?}", event), "Click { x: 3, button: 0x0a }");
+        } else {
+            assert_eq!(format!("{:?}", event), format!("{:?}", std_event));
+            assert_eq!(format!("{:#?}", event), format!("{:#?}", std_event));
+        }
+    }
+
+    let meters = Meters(1.0, 2.0);
+    assert_eq!(format!("{:#?}", meters), format!("{:#?}", std_derive::Meters(1.0, 2.0)));
+}
@@ -0,0 +1,56 @@
+// Fields can be left out with #[debug(skip)], hidden with #[debug(redact)],
+// printed under another name with #[debug(rename = "...")], or formatted by a
+// function with #[debug(with = "...")].
+//
+// Skipped, redacted and delegated fields are not printed with their Debug
+// impl, so their type does not need one.
+
+use derive_debug::CustomDebug;
+use std::fmt::Debug;
+
+mod hex {
+    use std::fmt;
+
+    pub fn fmt_bytes(bytes: &[u8; 4], f: &mut fmt::Formatter) -> fmt::Result {
+        for byte in bytes {
+            write!(f, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+pub struct NotDebug;
+
+#[derive(CustomDebug)]
+pub struct Account<T> {
+    #[debug(rename = "id")]
+    account_id: u32,
+    #[debug(redact)]
+    password: String,
+    #[debug(skip)]
+    cache: T,
+    #[debug(with = "hex::fmt_bytes")]
+    key: [u8; 4],
+}
+
+#[derive(CustomDebug)]
+pub enum Credential {
+    Token(#[debug(redact)] String),
+    Anonymous { #[debug(skip)] session: NotDebug, #[debug(rename = "r#type")] kind: u8 },
+}
+
+fn assert_debug<F: Debug>() {}
+
+fn main() {
+    assert_debug::<Account<NotDebug>>();
+
+    let account = Account { account_id: 7, password: "hunter2".to_owned(), cache: NotDebug, key: [0xde, 0xad, 0xbe, 0xef] };
+    let debug = format!("{:?}", account);
+    assert_eq!(debug, r#"Account { id: 7, password: <redacted>, key: deadbeef }"#);
+
+    let token = Credential::Token("secret".to_owned());
+    assert_eq!(format!("{:?}", token), "Token(<redacted>)");
+
+    let anonymous = Credential::Anonymous { session: NotDebug, kind: 1 };
+    assert_eq!(format!("{:?}", anonymous), "Anonymous { r#type: 1 }");
+}
@@ -0,0 +1,21 @@
+// A field is printed in a single way, so the options choosing how are
+// exclusive. Tuple fields have no name to rename.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+pub struct Secret {
+    #[debug(redact, skip)]
+    password: String,
+}
+
+#[derive(CustomDebug)]
+pub struct Pair(#[debug(rename = "first")] u8, u8);
+
+#[derive(CustomDebug)]
+pub struct Unknown {
+    #[debug(hide)]
+    value: u8,
+}
+
+fn main() {}
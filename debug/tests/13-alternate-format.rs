@@ -0,0 +1,48 @@
+// Custom formats keep working under `{:#?}`, where the struct is printed on
+// several lines. Debug placeholders of the format are pretty-printed too, and
+// indented along with the rest of the struct.
+//
+// A format string may also print other expressions than the field, given
+// after it like in `format_args!`, e.g. to print a ratio from two fields.
+
+use derive_debug::CustomDebug;
+
+#[derive(Debug)]
+pub struct Point {
+    x: i32,
+    y: i32,
+}
+
+#[derive(CustomDebug)]
+pub struct Shape {
+    #[debug = "0b{:08b}"]
+    flags: u8,
+    #[debug = "at {:?}"]
+    origin: Point,
+    #[debug("{}/{}", self.num, self.den)]
+    ratio: (),
+    #[debug(skip)]
+    num: u32,
+    #[debug(skip)]
+    den: u32,
+}
+
+fn main() {
+    let shape = Shape { flags: 5, origin: Point { x: 1, y: 2 }, ratio: (), num: 3, den: 4 };
+
+    let debug = format!("{:?}", shape);
+    let expected = "Shape { flags: 0b00000101, origin: at Point { x: 1, y: 2 }, ratio: 3/4 }";
+    assert_eq!(debug, expected);
+
+    let debug = format!("{:#?}", shape);
+    let expected = "\
+Shape {
+    flags: 0b00000101,
+    origin: at Point {
+        x: 1,
+        y: 2,
+    },
+    ratio: 3/4,
+}";
+    assert_eq!(debug, expected);
+}
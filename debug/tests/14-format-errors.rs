@@ -0,0 +1,24 @@
+// The format string of a field prints the field, so it must have exactly one
+// placeholder, and that placeholder can not refer to anything else.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+pub struct Field {
+    #[debug = "0b{:08b} ({})"]
+    bitmask: u8,
+}
+
+#[derive(CustomDebug)]
+pub struct Label {
+    #[debug = "label"]
+    text: &'static str,
+}
+
+#[derive(CustomDebug)]
+pub struct Width {
+    #[debug = "{:>width$}"]
+    text: &'static str,
+}
+
+fn main() {}
@@ -0,0 +1,51 @@
+// CustomDisplay implements Display from a template given on the struct, or on
+// each variant of an enum. Placeholders name the fields to interpolate, or give
+// their index for tuple fields, with the usual format specs.
+//
+// Bounds are inferred like for CustomDebug, using the trait of each placeholder:
+// here `T: Display` for the name and `T: LowerHex` for the code.
+
+use derive_debug::CustomDisplay;
+use std::fmt::{Display, LowerHex};
+
+#[derive(CustomDisplay)]
+#[display("{name} ({id})")]
+pub struct User {
+    name: String,
+    id: u32,
+    #[allow(dead_code)]
+    password: String,
+}
+
+#[derive(CustomDisplay)]
+pub enum Error<T> {
+    #[display("not found: {path}")]
+    NotFound { path: String },
+    #[display("status {0} at {1:#06x}")]
+    Status(T, T),
+    #[display("disk full, {{retry}}")]
+    DiskFull,
+}
+
+fn assert_display<T: Display>() {}
+
+fn main() {
+    let user = User {
+        name: "ferris".to_owned(),
+        id: 7,
+        password: "hunter2".to_owned(),
+    };
+    assert_eq!(user.to_string(), "ferris (7)");
+
+    let error: Error<u16> = Error::NotFound { path: "/tmp".to_owned() };
+    assert_eq!(error.to_string(), "not found: /tmp");
+    let error: Error<u16> = Error::Status(404, 255);
+    assert_eq!(error.to_string(), "status 404 at 0x00ff");
+    let error: Error<u16> = Error::DiskFull;
+    assert_eq!(error.to_string(), "disk full, {retry}");
+
+    fn assert_bounds<T: Display + LowerHex>() {
+        assert_display::<Error<T>>();
+    }
+    assert_bounds::<u8>();
+}
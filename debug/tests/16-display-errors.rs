@@ -0,0 +1,32 @@
+// Every struct and variant needs a template, and its placeholders must name a
+// field: positional placeholders and unknown names are rejected.
+
+use derive_debug::CustomDisplay;
+
+#[derive(CustomDisplay)]
+pub struct Missing {
+    name: String,
+}
+
+#[derive(CustomDisplay)]
+pub enum Partial {
+    #[display("first")]
+    First,
+    Second,
+}
+
+#[derive(CustomDisplay)]
+#[display("{} ({})")]
+pub struct Positional {
+    name: String,
+    id: u32,
+}
+
+#[derive(CustomDisplay)]
+#[display("{name} ({identifier})")]
+pub struct Unknown {
+    name: String,
+    id: u32,
+}
+
+fn main() {}
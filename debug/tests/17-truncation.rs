@@ -0,0 +1,56 @@
+// Large collections can be truncated with #[debug(limit = N)], printing at most
+// N elements followed by `..`, or summarized with #[debug(len_only)] as their
+// length. #[debug(non_exhaustive)] on the struct or enum ends the output of
+// every shape with `..`, like `finish_non_exhaustive`.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+pub struct Packet<'a> {
+    id: u32,
+    #[debug(limit = 4)]
+    payload: Vec<u8>,
+    #[debug(limit = 4)]
+    checksum: &'a [u8],
+    #[debug(len_only)]
+    trailer: Vec<u8>,
+}
+
+#[derive(CustomDebug)]
+#[debug(non_exhaustive)]
+pub struct Connection {
+    peer: &'static str,
+    #[debug(skip)]
+    #[allow(dead_code)]
+    socket: u32,
+}
+
+#[derive(CustomDebug)]
+#[debug(non_exhaustive)]
+pub enum State {
+    Open(u16),
+    Closed,
+}
+
+fn main() {
+    let packet = Packet {
+        id: 1,
+        payload: (0..10).collect(),
+        checksum: &[1, 2, 3],
+        trailer: vec![0; 1234],
+    };
+    assert_eq!(
+        format!("{:?}", packet),
+        "Packet { id: 1, payload: [0, 1, 2, 3, ..], checksum: [1, 2, 3], trailer: len=1234 }",
+    );
+    assert_eq!(
+        format!("{:#?}", packet),
+        "Packet {\n    id: 1,\n    payload: [\n        0,\n        1,\n        2,\n        3,\n        ..\n    ],\n    checksum: [\n        1,\n        2,\n        3,\n    ],\n    trailer: len=1234,\n}",
+    );
+
+    let connection = Connection { peer: "localhost", socket: 3 };
+    assert_eq!(format!("{:?}", connection), r#"Connection { peer: "localhost", .. }"#);
+
+    assert_eq!(format!("{:?}", State::Open(80)), "Open(80, ..)");
+    assert_eq!(format!("{:?}", State::Closed), "Closed { .. }");
+}
@@ -0,0 +1,80 @@
+// The generated impls only use `core::fmt` and never allocate, so CustomDebug
+// and CustomDisplay work in `#![no_std]` crates. Paths are fully qualified, so
+// they also work without the prelude.
+//
+// The standard library is only linked from a module, to run the test: it is not
+// in scope for the derived types. Output is written to a fixed-size buffer.
+
+#![no_std]
+
+mod runtime {
+    extern crate std;
+}
+
+#[no_implicit_prelude]
+mod types {
+    #[derive(::derive_debug::CustomDebug)]
+    pub struct Register<'a> {
+        #[debug = "0x{:02x}"]
+        pub address: u8,
+        #[debug = "{:?}"]
+        pub name: &'a str,
+        #[debug(limit = 2)]
+        pub values: &'a [u16],
+        #[debug(len_only)]
+        pub history: &'a [u16],
+        #[debug(with = "super::fmt_flag")]
+        pub flag: bool,
+    }
+
+    #[derive(::derive_debug::CustomDebug, ::derive_debug::CustomDisplay)]
+    #[debug(non_exhaustive)]
+    pub enum Mode<T> {
+        #[display("read {0}")]
+        Read(T),
+        #[display("off")]
+        Off,
+    }
+}
+
+fn fmt_flag(flag: &bool, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+    f.write_str(if *flag { "on" } else { "off" })
+}
+
+struct Buffer {
+    bytes: [u8; 256],
+    len: usize,
+}
+
+impl core::fmt::Write for Buffer {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        let end: usize = self.len + s.len();
+        self.bytes.get_mut(self.len..end).ok_or(core::fmt::Error)?.copy_from_slice(s.as_bytes());
+        self.len = end;
+        Ok(())
+    }
+}
+
+fn check(args: core::fmt::Arguments, expected: &str) {
+    let mut buffer = Buffer { bytes: [0; 256], len: 0 };
+    core::fmt::Write::write_fmt(&mut buffer, args).unwrap();
+    assert_eq!(core::str::from_utf8(&buffer.bytes[..buffer.len]).unwrap(), expected);
+}
+
+fn main() {
+    let register = types::Register {
+        address: 10,
+        name: "status",
+        values: &[1, 2, 3],
+        history: &[4, 5],
+        flag: true,
+    };
+    check(
+        format_args!("{:?}", register),
+        r#"Register { address: 0x0a, name: "status", values: [1, 2, ..], history: len=2, flag: on }"#,
+    );
+
+    check(format_args!("{:?}", types::Mode::Read(3)), "Read(3, ..)");
+    check(format_args!("{}", types::Mode::Read(3)), "read 3");
+    check(format_args!("{}", types::Mode::<u8>::Off), "off");
+}
@@ -0,0 +1,65 @@
+// Bounds are inferred through the whole field type: the type parameters of
+// references, arrays, slices, tuples and nested generic types are bounded, like
+// `T: Debug` for a `Vec<Option<T>>` field.
+//
+// PhantomData is recognized wherever it appears, as are pointers and function
+// pointers, which are printed without their type parameters being Debug.
+//
+// When a field uses an associated type of a type parameter, the bound is on the
+// whole field type instead, like `Vec<Option<T::Value>>: Debug`.
+
+use derive_debug::CustomDebug;
+use std::fmt::Debug;
+use std::marker::{PhantomData, PhantomPinned};
+
+#[derive(CustomDebug)]
+pub struct Nested<'a, A, B, C, D, E, F, G> {
+    boxed: Box<A>,
+    list: Vec<Option<B>>,
+    array: [C; 2],
+    pair: (D, u8),
+    reference: &'a E,
+    marker: (PhantomData<fn() -> F>, PhantomPinned),
+    pointer: *const G,
+}
+
+pub trait Trait {
+    type Value;
+}
+
+#[derive(CustomDebug)]
+pub struct Projection<T: Trait> {
+    values: Vec<Option<T::Value>>,
+    qualified: Box<<T as Trait>::Value>,
+    marker: PhantomData<T>,
+}
+
+fn assert_debug<F: Debug>() {}
+
+fn main() {
+    // Does not implement Debug.
+    struct NotDebug;
+
+    impl Trait for NotDebug {
+        type Value = u8;
+    }
+
+    assert_debug::<Nested<u8, u8, u8, u8, u8, NotDebug, NotDebug>>();
+    assert_debug::<Projection<NotDebug>>();
+
+    let value: u8 = 5;
+    let nested = Nested::<u8, u8, u8, u8, u8, u8, u8> {
+        boxed: Box::new(1),
+        list: vec![Some(2), None],
+        array: [3, 4],
+        pair: (5, 6),
+        reference: &value,
+        marker: (PhantomData, PhantomPinned),
+        pointer: std::ptr::null(),
+    };
+    assert_eq!(
+        format!("{:?}", nested),
+        "Nested { boxed: 1, list: [Some(2), None], array: [3, 4], pair: (5, 6), reference: 5, \
+         marker: (PhantomData<fn() -> u8>, PhantomPinned), pointer: 0x0 }",
+    );
+}
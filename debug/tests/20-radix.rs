@@ -0,0 +1,48 @@
+// Shorthands for the usual register and buffer formats: #[debug(hex)],
+// #[debug(bin)] and #[debug(octal)] print integers with a prefix, padded to the
+// width of their type, and collections of integers as a list of those.
+// #[debug(bytes)] prints a byte collection as an escaped byte string.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+pub struct Registers<'a> {
+    #[debug(hex)]
+    status: u8,
+    #[debug(hex)]
+    address: u32,
+    #[debug(bin)]
+    flags: u16,
+    #[debug(octal)]
+    mode: u16,
+    #[debug(hex)]
+    offset: usize,
+    #[debug(hex)]
+    key: [u8; 3],
+    #[debug(bin)]
+    masks: &'a [u8],
+    #[debug(bytes)]
+    name: Vec<u8>,
+    #[debug(bytes)]
+    raw: &'a [u8],
+}
+
+fn main() {
+    let registers = Registers {
+        status: 10,
+        address: 0xbeef,
+        flags: 5,
+        mode: 0o644,
+        offset: 255,
+        key: [1, 0xab, 0xff],
+        masks: &[3],
+        name: b"eth0".to_vec(),
+        raw: b"a\"\x00\xff",
+    };
+
+    assert_eq!(
+        format!("{:?}", registers),
+        "Registers { status: 0x0a, address: 0x0000beef, flags: 0b0000000000000101, mode: 0o000644, \
+         offset: 0xff, key: [0x01, 0xab, 0xff], masks: [0b00000011], name: b\"eth0\", raw: b\"a\\\"\\x00\\xff\" }",
+    );
+}
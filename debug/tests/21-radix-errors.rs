@@ -0,0 +1,18 @@
+// The hex, bin and octal shorthands need to know the width of the integers
+// they print, so they are only supported on integers and collections of them.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+pub struct Reading {
+    #[debug(hex)]
+    value: f32,
+}
+
+#[derive(CustomDebug)]
+pub struct Label {
+    #[debug(bin)]
+    text: String,
+}
+
+fn main() {}
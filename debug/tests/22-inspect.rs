@@ -0,0 +1,70 @@
+// With #[debug(inspect)], CustomDebug also implements the Inspect trait of the
+// derive_debug_inspect runtime crate, which walks the fields printed by Debug
+// as (name, value) pairs. This renders the same type in other shapes, like
+// key=value log lines, without a second derive.
+//
+// Field options apply to both: skipped fields are not visited, renamed fields
+// are visited under their new name, and values use their custom format. Tuple
+// fields are named by their index.
+
+use derive_debug::CustomDebug;
+use derive_debug_inspect::{Inspect, KeyValues};
+use std::fmt::Debug;
+
+#[derive(CustomDebug)]
+#[debug(inspect)]
+pub struct Request<T> {
+    #[debug(rename = "request_id")]
+    id: u32,
+    #[debug = "{:?}ms"]
+    latency: u64,
+    #[debug(redact)]
+    token: String,
+    #[debug(skip)]
+    #[allow(dead_code)]
+    cache: Vec<u8>,
+    body: T,
+}
+
+#[derive(CustomDebug)]
+#[debug(inspect)]
+pub enum Event {
+    Connect(&'static str, #[debug(hex)] u16),
+    Disconnect,
+}
+
+fn fields(value: &dyn Inspect) -> Vec<(String, String)> {
+    let mut fields: Vec<(String, String)> = Vec::new();
+    value.inspect(&mut |name: &str, value: &dyn Debug| fields.push((name.to_owned(), format!("{:?}", value))));
+    fields
+}
+
+fn main() {
+    let request = Request {
+        id: 7,
+        latency: 12,
+        token: "secret".to_owned(),
+        cache: Vec::new(),
+        body: "ok",
+    };
+    assert_eq!(request.name(), "Request");
+    assert_eq!(
+        fields(&request),
+        [
+            ("request_id".to_owned(), "7".to_owned()),
+            ("latency".to_owned(), "12ms".to_owned()),
+            ("token".to_owned(), "<redacted>".to_owned()),
+            ("body".to_owned(), "\"ok\"".to_owned()),
+        ],
+    );
+    assert_eq!(
+        KeyValues(&request).to_string(),
+        r#"request_id=7 latency=12ms token=<redacted> body="ok""#,
+    );
+
+    let event = Event::Connect("localhost", 80);
+    assert_eq!(event.name(), "Connect");
+    assert_eq!(KeyValues(&event).to_string(), r#"0="localhost" 1=0x0050"#);
+    assert_eq!(Event::Disconnect.name(), "Disconnect");
+    assert_eq!(KeyValues(&Event::Disconnect).to_string(), "");
+}
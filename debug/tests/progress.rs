@@ -1,12 +1,26 @@
 #[test]
 fn tests() {
     let t = trybuild::TestCases::new();
-    //t.pass("tests/01-parse.rs");
-    //t.pass("tests/02-impl-debug.rs");
-    //t.pass("tests/03-custom-format.rs");
-    //t.pass("tests/04-type-parameter.rs");
-    //t.pass("tests/05-phantom-data.rs");
-    //t.pass("tests/06-bound-trouble.rs");
-    //t.pass("tests/07-associated-type.rs");
-    //t.pass("tests/08-escape-hatch.rs");
+    t.pass("tests/01-parse.rs");
+    t.pass("tests/02-impl-debug.rs");
+    t.pass("tests/03-custom-format.rs");
+    t.pass("tests/04-type-parameter.rs");
+    t.pass("tests/05-phantom-data.rs");
+    t.pass("tests/06-bound-trouble.rs");
+    t.pass("tests/07-associated-type.rs");
+    t.pass("tests/08-escape-hatch.rs");
+    t.pass("tests/09-field-bound.rs");
+    t.pass("tests/10-enums-and-tuples.rs");
+    t.pass("tests/11-field-options.rs");
+    t.compile_fail("tests/12-field-options-errors.rs");
+    t.pass("tests/13-alternate-format.rs");
+    t.compile_fail("tests/14-format-errors.rs");
+    t.pass("tests/15-display.rs");
+    t.compile_fail("tests/16-display-errors.rs");
+    t.pass("tests/17-truncation.rs");
+    t.pass("tests/18-no-std.rs");
+    t.pass("tests/19-nested-bounds.rs");
+    t.pass("tests/20-radix.rs");
+    t.compile_fail("tests/21-radix-errors.rs");
+    t.pass("tests/22-inspect.rs");
 }
@@ -0,0 +1,107 @@
+//! Reorder the variants of #[sorted] enums and the arms of #[sorted] match expressions in place.
+//!
+//! Usage: cargo sorted [--check] [PATH]...
+//!
+//! Paths may be files or directories, searched recursively for `.rs` files, and default to `src`.
+//! With `--check`, files are left untouched and the command fails if any of them would be rewritten.
+
+#[path = "../../src/order.rs"]
+mod order;
+#[path = "../../src/pattern.rs"]
+mod pattern;
+mod rewrite;
+
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::{env, fs, io};
+
+use crate::rewrite::sort_source;
+
+fn main() -> ExitCode {
+    let mut args: Vec<String> = env::args().skip(1).collect();
+
+    // Invoked as `cargo sorted`, cargo passes the subcommand name first
+    if args.first().map(String::as_str) == Some("sorted") {
+        args.remove(0);
+    }
+
+    let check: bool = args.iter().any(|arg| arg == "--check");
+    let mut roots: Vec<PathBuf> = args
+        .iter()
+        .filter(|arg| *arg != "--check")
+        .map(PathBuf::from)
+        .collect();
+    if roots.is_empty() {
+        roots.push(PathBuf::from("src"));
+    }
+
+    let mut files: Vec<PathBuf> = Vec::new();
+    for root in &roots {
+        if let Err(error) = collect_rust_files(root, &mut files) {
+            eprintln!("error: {}: {}", root.display(), error);
+            return ExitCode::FAILURE
+        }
+    }
+
+    let mut success: bool = true;
+    for file in &files {
+        match sort_file(file, check) {
+            Ok(false) => {},
+            Ok(true) if check => {
+                println!("{} is not sorted", file.display());
+                success = false;
+            },
+            Ok(true) => println!("sorted {}", file.display()),
+            Err(error) => {
+                eprintln!("error: {}: {}", file.display(), error);
+                success = false;
+            },
+        }
+    }
+
+    if success { ExitCode::SUCCESS } else { ExitCode::FAILURE }
+}
+
+/// Push the path if it is a Rust file, or every Rust file under it if it is a directory.
+fn collect_rust_files(path: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    if !path.is_dir() {
+        fs::metadata(path)?;
+        files.push(path.to_path_buf());
+        return Ok(())
+    }
+
+    let mut entries: Vec<PathBuf> = fs::read_dir(path)?
+        .map(|entry| entry.map(|x| x.path()))
+        .collect::<io::Result<_>>()?;
+    entries.sort();
+
+    for entry in entries {
+        if entry.is_dir() || entry.extension().is_some_and(|ext| ext == "rs") {
+            collect_rust_files(&entry, files)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Sort the file, writing it back unless only checking.
+///
+/// Return true if the file was not sorted.
+fn sort_file(path: &Path, check: bool) -> Result<bool, Box<dyn std::error::Error>> {
+    let source: String = fs::read_to_string(path)?;
+    let (sorted, warnings) = sort_source(&source)?;
+
+    for warning in warnings {
+        eprintln!("warning: {}:{}", path.display(), warning);
+    }
+
+    if sorted == source {
+        return Ok(false)
+    }
+
+    if !check {
+        fs::write(path, sorted)?;
+    }
+
+    Ok(true)
+}
@@ -0,0 +1,344 @@
+use std::cmp::Ordering;
+use std::ops::Range;
+
+use proc_macro2::{LineColumn, Span, TokenStream};
+use quote::{ToTokens, TokenStreamExt};
+use syn::visit::{self, Visit};
+use syn::{Attribute, ExprMatch, ItemEnum, Meta};
+
+use crate::order::{has_fixed_discriminants, is_section_attribute, SortOrder};
+use crate::pattern::{arm_key, ArmKey};
+
+/// A sortable element of a list, split into the pieces that move along with it.
+struct Element {
+    lead: String,  // Whitespace, comments, attributes and the element itself
+    trail: String,  // Comment on the same line, after the separator
+    has_comma: bool,
+    body_offset: usize,  // Offset of the first token in the lead
+    marker: Option<String>,  // Section marker, removed from the lead since it stays at the start of its section
+}
+
+/// Replacement of a source range by its sorted counterpart.
+struct Edit {
+    start: usize,
+    end: usize,
+    replacement: String,
+}
+
+/// Collect the reordering edits of every #[sorted] enum and match expression of a file.
+struct CollectEdits<'a> {
+    source: &'a str,
+    line_starts: Vec<usize>,
+    edits: Vec<Edit>,
+    warnings: Vec<String>,  // Lists left untouched, prefixed with their line and column
+}
+
+/// Maximum number of rewriting passes, each one sorting the innermost lists left.
+const MAX_PASSES: usize = 64;
+
+/// Sort every #[sorted] enum and match expression of the given source.
+///
+/// Return the rewritten source, identical to the input if everything is already sorted, along with the warnings
+/// about the lists which can not be sorted safely.
+/// Return an error if the order does not settle after MAX_PASSES passes.
+pub(crate) fn sort_source(source: &str) -> Result<(String, Vec<String>), syn::Error> {
+    let mut source: String = source.to_string();
+
+    // Nested lists are rewritten in several passes, since overlapping edits can not be applied together
+    for _ in 0..MAX_PASSES {
+        let file: syn::File = syn::parse_file(&source)?;
+
+        let mut collector: CollectEdits = CollectEdits::new(&source);
+        collector.visit_file(&file);
+        let mut edits: Vec<Edit> = collector.edits;
+        let warnings: Vec<String> = collector.warnings;
+
+        // Lists left untouched are reported once, at their final position
+        if edits.is_empty() {
+            return Ok((source, warnings))
+        }
+
+        // Keep the innermost edits first, then apply from the end of the file to keep offsets valid
+        edits.sort_by_key(|edit| edit.end - edit.start);
+        let mut accepted: Vec<Edit> = Vec::new();
+        for edit in edits {
+            if accepted.iter().all(|other| edit.end <= other.start || other.end <= edit.start) {
+                accepted.push(edit);
+            }
+        }
+        accepted.sort_by_key(|edit| std::cmp::Reverse(edit.start));
+
+        for edit in accepted {
+            source.replace_range(edit.start..edit.end, &edit.replacement);
+        }
+    }
+
+    Err(syn::Error::new(
+        Span::call_site(), format!("the order did not settle after {} passes, the file is left untouched", MAX_PASSES)
+    ))
+}
+
+/// Return the ordering options of the #[sorted] attribute, if any.
+///
+/// Attributes with invalid options are ignored, the macro itself reports them.
+fn sorted_attribute_order(attrs: &[Attribute]) -> Option<SortOrder> {
+    let attribute: &Attribute = attrs.iter().find(|attribute| {
+        let segments: Vec<String> = attribute.path().segments
+            .iter()
+            .map(|x| format!("{}", x.ident))
+            .collect();
+
+        segments == ["sorted"] || segments == ["sorted", "sorted"]
+    })?;
+
+    let mut order: SortOrder = SortOrder::default();
+    if let Meta::List(_) = &attribute.meta {
+        attribute.parse_nested_meta(|meta| order.parse_meta(meta)).ok()?;
+    }
+
+    Some(order)
+}
+
+impl<'a> CollectEdits<'a> {
+    fn new(source: &'a str) -> Self {
+        let line_starts: Vec<usize> = std::iter::once(0)
+            .chain(source.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+
+        CollectEdits {
+            source,
+            line_starts,
+            edits: Vec::new(),
+            warnings: Vec::new(),
+        }
+    }
+
+    /// Convert a line/column location into a byte offset of the source.
+    fn offset(&self, location: LineColumn) -> usize {
+        let line_start: usize = self.line_starts[location.line - 1];
+        self.source[line_start..]
+            .char_indices()
+            .nth(location.column)
+            .map_or(self.source.len(), |(i, _)| line_start + i)
+    }
+
+    /// Byte offset of the start of the first token.
+    fn start_of(&self, tokens: TokenStream) -> usize {
+        let first: Span = tokens.into_iter().next().expect("empty element").span();
+        self.offset(first.start())
+    }
+
+    /// Byte offset of the end of the last token.
+    fn end_of(&self, tokens: TokenStream) -> usize {
+        let last: Span = tokens.into_iter().last().expect("empty element").span();
+        self.offset(last.end())
+    }
+
+    /// Return the length of a same-line comment following the position, including the whitespace before it.
+    fn trailing_comment_len(&self, position: usize) -> usize {
+        let rest: &str = &self.source[position..];
+        let line: &str = &rest[..rest.find('\n').unwrap_or(rest.len())];
+
+        if line.trim_start().starts_with("//") {
+            line.trim_end().len()
+        } else {
+            0
+        }
+    }
+
+    /// Build an element from its start, its tokens and the end of the comma following it.
+    ///
+    /// Also return the start of the next element.
+    fn element(&self, start: usize, tokens: TokenStream, comma_end: Option<usize>) -> (Element, usize) {
+        let body_start: usize = self.start_of(tokens.clone());
+        let end: usize = self.end_of(tokens);
+        let after_comma: usize = comma_end.unwrap_or(end);
+        let trail_len: usize = self.trailing_comment_len(after_comma);
+
+        let element: Element = Element {
+            lead: self.source[start..end].to_string(),
+            trail: self.source[after_comma..after_comma + trail_len].to_string(),
+            has_comma: comma_end.is_some(),
+            body_offset: body_start - start,
+            marker: None,
+        };
+
+        (element, after_comma + trail_len)
+    }
+
+    /// Move the section marker of the element out of its lead, along with the whitespace following it.
+    fn take_marker(&self, element: &mut Element, start: usize, marker: &Attribute) {
+        let marker_start: usize = self.offset(marker.pound_token.span.start()) - start;
+        let marker_end: usize = self.offset(marker.bracket_token.span.close().end()) - start;
+        let whitespace_len: usize = element.lead[marker_end..].len() - element.lead[marker_end..].trim_start().len();
+
+        let marker_text: String = element.lead
+            .drain(marker_start..marker_end + whitespace_len)
+            .collect();
+        element.body_offset = element.body_offset.min(marker_start);
+        element.marker = Some(marker_text);
+    }
+
+    /// Record an edit if the elements are not in their sorted order, given as the index of the element to put at
+    /// each position.
+    ///
+    /// Section markers stay on whichever element comes first in the section.
+    /// Enum commas are positional, since the trailing comma belongs to the list.
+    /// Match arm commas follow their arm, since block bodies do not need one, and are added when an arm moves
+    /// away from the last position.
+    fn push_edit(&mut self, start: usize, end: usize, elements: Vec<Element>, sorted_indices: &[usize], positional_commas: bool) {
+        if sorted_indices.iter().copied().eq(0..elements.len()) {
+            return
+        }
+
+        let last_index: usize = elements.len() - 1;
+        let mut replacement: String = String::new();
+
+        for (index, element) in sorted_indices.iter().map(|x| &elements[*x]).enumerate() {
+            let has_comma: bool = if positional_commas {
+                elements[index].has_comma
+            } else {
+                element.has_comma || index != last_index
+            };
+
+            match &elements[index].marker {
+                Some(marker) => {
+                    replacement.push_str(&element.lead[..element.body_offset]);
+                    replacement.push_str(marker);
+                    replacement.push_str(&element.lead[element.body_offset..]);
+                },
+                None => replacement.push_str(&element.lead),
+            }
+            if has_comma {
+                replacement.push(',');
+            }
+            replacement.push_str(&element.trail);
+        }
+
+        self.edits.push(Edit { start, end, replacement });
+    }
+}
+
+/// Return the index of the element to put at each position once sorted, comparing the elements by index.
+///
+/// Each set of positions is sorted independently, elements outside of them keep their position.
+fn sorted_indices<F>(len: usize, position_sets: &[Vec<usize>], compare: F) -> Vec<usize>
+where
+    F: Fn(usize, usize) -> Ordering,
+{
+    let mut sorted_indices: Vec<usize> = (0..len).collect();
+    for positions in position_sets {
+        let mut set_indices: Vec<usize> = positions.clone();
+        set_indices.sort_by(|a, b| compare(*a, *b));
+        for (position, index) in positions.iter().zip(set_indices) {
+            sorted_indices[*position] = index;
+        }
+    }
+
+    sorted_indices
+}
+
+impl<'ast> Visit<'ast> for CollectEdits<'_> {
+    fn visit_item_enum(&mut self, i: &'ast ItemEnum) {
+        // Enums only checked for their derive list keep their variants in place
+        // Reordering would change the discriminants of the variants, like #[sorted(fix)] enums with a #[repr] or
+        // explicit discriminants, and the implicit ones of enums sorted by discriminant
+        let order: Option<SortOrder> = sorted_attribute_order(&i.attrs).filter(|order| {
+            !order.derives && !order.by_discriminant && !has_fixed_discriminants(&i.attrs, &i.variants)
+        });
+
+        if let (Some(order), false) = (order, i.variants.is_empty()) {
+            let names: Vec<String> = i.variants.iter().map(|x| format!("{}", x.ident)).collect();
+            let region_start: usize = self.offset(i.brace_token.span.open().end());
+            let mut start: usize = region_start;
+            let mut elements: Vec<Element> = Vec::new();
+            let mut sections: Vec<Range<usize>> = Vec::new();
+            sections.push(0..i.variants.len());
+
+            for (index, pair) in i.variants.pairs().enumerate() {
+                let variant = pair.value();
+                let comma_end: Option<usize> = pair.punct().map(|comma| self.offset(comma.span.end()));
+                let (mut element, next_start) = self.element(start, variant.to_token_stream(), comma_end);
+
+                // Markers only start a new section when grouping
+                let marker: Option<&Attribute> = variant.attrs.iter().find(|x| is_section_attribute(x));
+                if let (true, Some(marker)) = (order.groups, marker) {
+                    self.take_marker(&mut element, start, marker);
+                    if index != 0 {
+                        sections.last_mut().unwrap().end = index;
+                        sections.push(index..i.variants.len());
+                    }
+                }
+
+                elements.push(element);
+                start = next_start;
+            }
+
+            let position_sets: Vec<Vec<usize>> = sections.into_iter().map(|x| x.collect()).collect();
+            let sorted_indices: Vec<usize> = sorted_indices(elements.len(), &position_sets, |a, b| {
+                order.compare_names(&names[a], &names[b])
+            });
+            self.push_edit(region_start, start, elements, &sorted_indices, true);
+        }
+
+        visit::visit_item_enum(self, i);
+    }
+
+    fn visit_expr_match(&mut self, i: &'ast ExprMatch) {
+        // Matches with patterns that can not be compared are left untouched, the macro reports them
+        // Matches following the order of an enum are left untouched too, the enum may be in another file
+        let order: Option<SortOrder> = sorted_attribute_order(&i.attrs).filter(|order| order.id.is_none());
+        let keys: Option<Vec<ArmKey>> = order.as_ref().and_then(|order| {
+            i.arms
+                .iter()
+                .map(|arm| arm_key(&arm.pat, order, &mut Vec::new()).ok())
+                .collect()
+        });
+
+        if let (Some(order), Some(keys), false) = (order, keys, i.arms.is_empty()) {
+            let region_start: usize = self.offset(i.brace_token.span.open().end());
+            let mut start: usize = region_start;
+            let mut elements: Vec<Element> = Vec::new();
+
+            for arm in &i.arms {
+                let mut tokens: TokenStream = TokenStream::new();
+                tokens.append_all(&arm.attrs);
+                arm.pat.to_tokens(&mut tokens);
+                arm.body.to_tokens(&mut tokens);
+
+                let comma_end: Option<usize> = arm.comma.map(|comma| self.offset(comma.span.end()));
+                let (element, next_start) = self.element(start, tokens, comma_end);
+                elements.push(element);
+                start = next_start;
+            }
+
+            // Catch-all arms keep their position, moving them would change which arms are reachable
+            let sortable_arms: Vec<usize> = (0..i.arms.len()).filter(|x| !keys[*x].key.is_catch_all()).collect();
+            let sorted_indices: Vec<usize> = sorted_indices(elements.len(), &[sortable_arms], |a, b| {
+                keys[a].key.compare(&keys[b].key, &order)
+            });
+
+            // Arms which can match the same value must keep their relative order, the first one handles the value
+            let swapped_overlap: Option<(usize, usize)> = (0..sorted_indices.len()).find_map(|position| {
+                let first: usize = sorted_indices[position];
+                sorted_indices[position + 1..]
+                    .iter()
+                    .find(|x| **x < first && keys[**x].key.overlaps(&keys[first].key, true))
+                    .map(|x| (*x, first))
+            });
+
+            match swapped_overlap {
+                Some((a, b)) => {
+                    let location: LineColumn = i.match_token.span.start();
+                    self.warnings.push(format!(
+                        "{}:{}: cannot reorder safely, `{}` and `{}` can match the same value",
+                        location.line, location.column + 1, keys[a], keys[b]
+                    ));
+                },
+                None => self.push_edit(region_start, start, elements, &sorted_indices, false),
+            }
+        }
+
+        visit::visit_expr_match(self, i);
+    }
+}
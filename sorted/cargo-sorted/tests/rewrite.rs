@@ -0,0 +1,199 @@
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+
+const UNSORTED: &str = r#"use sorted::sorted;
+
+#[sorted]
+pub enum Error {
+    /// Formatting failed
+    Fmt(std::fmt::Error),
+    #[allow(dead_code)]
+    Utf8, // Not valid UTF-8
+    Io(std::io::Error),
+}
+
+#[sorted::check]
+fn describe(error: &Error) -> &str {
+    #[sorted]
+    match error {
+        Error::Utf8 => "utf8",
+        Error::Io(_) => {
+            "io"
+        }
+        Error::Fmt(_) => "fmt"
+    }
+}
+"#;
+
+const SORTED: &str = r#"use sorted::sorted;
+
+#[sorted]
+pub enum Error {
+    /// Formatting failed
+    Fmt(std::fmt::Error),
+    Io(std::io::Error),
+    #[allow(dead_code)]
+    Utf8, // Not valid UTF-8
+}
+
+#[sorted::check]
+fn describe(error: &Error) -> &str {
+    #[sorted]
+    match error {
+        Error::Fmt(_) => "fmt",
+        Error::Io(_) => {
+            "io"
+        },
+        Error::Utf8 => "utf8",
+    }
+}
+"#;
+
+fn write_fixture(name: &str) -> PathBuf {
+    let path: PathBuf = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+    fs::write(&path, UNSORTED).unwrap();
+    path
+}
+
+#[test]
+fn rewrite_in_place() {
+    let path: PathBuf = write_fixture("rewrite_in_place.rs");
+
+    let status = Command::new(env!("CARGO_BIN_EXE_cargo-sorted"))
+        .arg("sorted")
+        .arg(&path)
+        .status()
+        .unwrap();
+
+    assert!(status.success());
+    assert_eq!(fs::read_to_string(&path).unwrap(), SORTED);
+}
+
+#[test]
+fn check_only() {
+    let path: PathBuf = write_fixture("check_only.rs");
+
+    let status = Command::new(env!("CARGO_BIN_EXE_cargo-sorted"))
+        .arg("--check")
+        .arg(&path)
+        .status()
+        .unwrap();
+
+    assert!(!status.success());
+    assert_eq!(fs::read_to_string(&path).unwrap(), UNSORTED);
+}
+
+#[test]
+fn rewrite_with_options() {
+    let path: PathBuf = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("rewrite_with_options.rs");
+    fs::write(&path, "#[sorted(natural)]\nenum Irq {\n    Irq10,\n    Irq2,\n}\n").unwrap();
+
+    let status = Command::new(env!("CARGO_BIN_EXE_cargo-sorted"))
+        .arg(&path)
+        .status()
+        .unwrap();
+
+    assert!(status.success());
+    assert_eq!(fs::read_to_string(&path).unwrap(), "#[sorted(natural)]\nenum Irq {\n    Irq2,\n    Irq10,\n}\n");
+}
+
+#[test]
+fn rewrite_sections() {
+    let path: PathBuf = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("rewrite_sections.rs");
+    fs::write(&path, r#"#[sorted(groups)]
+enum Error {
+    Internal,
+    Config,
+    #[sorted::section("network errors")]
+    /// Name resolution failed
+    Timeout,
+    Dns,
+}
+"#).unwrap();
+
+    let status = Command::new(env!("CARGO_BIN_EXE_cargo-sorted"))
+        .arg(&path)
+        .status()
+        .unwrap();
+
+    assert!(status.success());
+    assert_eq!(fs::read_to_string(&path).unwrap(), r#"#[sorted(groups)]
+enum Error {
+    Config,
+    Internal,
+    #[sorted::section("network errors")]
+    Dns,
+    /// Name resolution failed
+    Timeout,
+}
+"#);
+}
+
+#[test]
+fn implicit_discriminants_left_untouched() {
+    // Reordering would change the implicit discriminant of B, and the order would never settle
+    let path: PathBuf = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("implicit_discriminants_left_untouched.rs");
+    let source: &str = "#[sorted(by_discriminant, reverse)]\nenum E {\n    A = 10,\n    B,\n    C = 5,\n}\n";
+    fs::write(&path, source).unwrap();
+
+    let status = Command::new(env!("CARGO_BIN_EXE_cargo-sorted"))
+        .arg(&path)
+        .status()
+        .unwrap();
+
+    assert!(status.success());
+    assert_eq!(fs::read_to_string(&path).unwrap(), source);
+}
+
+#[test]
+fn explicit_discriminants_left_untouched() {
+    // Reordering would move Zed from 0 to 12
+    let path: PathBuf = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("explicit_discriminants_left_untouched.rs");
+    let source: &str = "#[sorted]\n#[repr(u8)]\nenum E {\n    Zed,\n    Alpha = 10,\n    Beta,\n}\n";
+    fs::write(&path, source).unwrap();
+
+    let status = Command::new(env!("CARGO_BIN_EXE_cargo-sorted"))
+        .arg(&path)
+        .status()
+        .unwrap();
+
+    assert!(status.success());
+    assert_eq!(fs::read_to_string(&path).unwrap(), source);
+}
+
+#[test]
+fn overlapping_arms_left_untouched() {
+    // Sorting would hide the arm of 5 behind the range, and change which arm handles Some(Kind::Data)
+    let path: PathBuf = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("overlapping_arms_left_untouched.rs");
+    let source: &str = r#"fn code(n: u8) -> u8 {
+    #[sorted]
+    match n {
+        5 => 0,
+        1..=9 => 1,
+        _ => 2,
+    }
+}
+
+fn kind(kind: Option<Kind>) -> u8 {
+    #[sorted]
+    match kind {
+        Some(_) => 0,
+        Some(Kind::Data) => 1,
+        None => 2,
+    }
+}
+"#;
+    fs::write(&path, source).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_cargo-sorted"))
+        .arg(&path)
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    assert_eq!(fs::read_to_string(&path).unwrap(), source);
+    let stderr: String = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains(":3:5: cannot reorder safely, `5` and `1..=9` can match the same value"), "{}", stderr);
+    assert!(stderr.contains(":12:5: cannot reorder safely, `Some(_)` and `Some(Kind::Data)` can match the same value"), "{}", stderr);
+}
@@ -1,38 +1,56 @@
+mod order;
+mod pattern;
 mod utils;
 mod visitor;
 
 use proc_macro::TokenStream;
 
 use proc_macro2::{Ident, Span, TokenStream as TokenStream2};
-use quote::{quote, ToTokens};
-use syn::{parse_macro_input, Item, ItemFn};
+use quote::{format_ident, quote};
+use std::cmp::Ordering;
+use std::ops::Range;
+
+use syn::{parse_macro_input, parse_quote, Attribute, Block, Expr, ExprArray, ExprLit, ExprReference, Fields, Item, ItemEnum, ItemMod};
+use syn::{ItemConst, ItemStatic, ItemStruct, ItemUnion, Lit, LitBool, LitStr, Path, UseGroup, UseTree, Variant};
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::token::Comma;
 use syn::visit_mut::VisitMut;
-use crate::utils::find_unsorted_element;
+use crate::order::{has_fixed_discriminants, is_section_attribute, SortOrder};
+use crate::utils::{combine_errors, discriminant_values, enum_sections, find_unsorted_elements, impl_item_ident, item_ident, list_tokens};
+use crate::utils::{remove_section_attributes, split_list, unsorted_error, unsorted_section_error, use_groups};
+use crate::utils::{report, use_tree_name, warn_by_default, Discriminant, Section};
 use crate::visitor::CheckSortedMatch;
 
-const ERROR_MESSAGE: &str = "expected enum or match expression";
+const ERROR_MESSAGE: &str =
+    "expected enum, struct with named fields, impl block, inline module, braced use list, string array or match expression";
 
 
 #[proc_macro_attribute]
-pub fn sorted(_args: TokenStream, input: TokenStream) -> TokenStream {
+pub fn sorted(args: TokenStream, input: TokenStream) -> TokenStream {
+    // Parse the ordering options
+    let mut order: SortOrder = SortOrder::default();
+    let order_parser = syn::meta::parser(|meta| order.parse_meta(meta));
+    parse_macro_input!(args with order_parser);
+
     // Parse the input tokens into a syntax tree
-    let input_item: Item = parse_macro_input!(input);
+    let mut input_item: Item = parse_macro_input!(input);
 
-    match _sorted(&input_item) {
-        // Hand the output tokens back to the compiler
-        Ok(_) => input_item.to_token_stream().into(),
-        Err(error) => {
-            let err: TokenStream2 = error.to_compile_error();
-            let result: TokenStream2 = quote! { #input_item #err };
-            result.into()
-        },
-    }
+    let sorted_result: Result<(), syn::Error> = fix_enum(&mut input_item, &order).and_then(|_| _sorted(&input_item, &order));
+    remove_section_attributes(&mut input_item);
+    let variants_macro: TokenStream2 = variants_macro(&input_item, &order);
+    let meta_impl: TokenStream2 = meta_impl(&input_item, &order);
+
+    // Hand the output tokens back to the compiler
+    let err: TokenStream2 = sorted_result.err().map_or(TokenStream2::new(), |error| report(error, order.warn || warn_by_default()));
+    let input_item: TokenStream2 = with_checks(&mut input_item, err);
+    quote! { #input_item #variants_macro #meta_impl }.into()
 }
 
 #[proc_macro_attribute]
 pub fn check(_args: TokenStream, input: TokenStream) -> TokenStream {
     // Parse the input tokens into a syntax tree
-    let input_item: ItemFn = parse_macro_input!(input);
+    let input_item: Item = parse_macro_input!(input);
 
     match _check(input_item) {
         // Hand the output tokens back to the compiler
@@ -46,44 +64,465 @@ pub fn check(_args: TokenStream, input: TokenStream) -> TokenStream {
 }
 
 
-fn _sorted(input_item: &Item) -> Result<(), syn::Error> {
-    // Only work on enums
-    if let Item::Enum(input_enum) = input_item.clone() {
+/// Check that comma-separated elements are sorted, and emit them unchanged.
+///
+/// If given a single macro invocation, like `sorted::check_list! { table! { ... } }`, the elements of its body
+/// are checked.
+#[proc_macro]
+pub fn check_list(input: TokenStream) -> TokenStream {
+    let input: TokenStream2 = input.into();
+    let elements: Vec<TokenStream2> = split_list(list_tokens(input.clone()));
+    let names: Vec<String> = elements.iter().map(|x| x.to_string()).collect();
+
+    match unsorted_error(&names, &elements, |a, b| a.cmp(b), false) {
+        None => input.into(),
+        Some(error) => {
+            let err: TokenStream2 = report(error, warn_by_default());
+            let result: TokenStream2 = quote! { #input #err };
+            result.into()
+        },
+    }
+}
+
+/// Check that the arms of a `#[sorted(id = "...")]` match follow the order of the enum sharing the id.
+///
+/// Invoked by the hidden macro generated along with the enum, which provides its variants:
+/// `__check_variants! { Id [Variant, ...] warn Variant { pattern }, ... }`, with `warn` a boolean literal.
+#[doc(hidden)]
+#[proc_macro]
+pub fn __check_variants(input: TokenStream) -> TokenStream {
+    let input: VariantsCheck = parse_macro_input!(input);
+
+    match _check_variants(&input) {
+        None => TokenStream::new(),
+        Some(error) => report(error, input.warn).into(),
+    }
+}
+
+
+fn _sorted(input_item: &Item, order: &SortOrder) -> Result<(), syn::Error> {
+    // Only enums have discriminants and sections
+    if order.by_discriminant && !matches!(input_item, Item::Enum(_)) {
+        return Err(syn::Error::new(Span::call_site(), "by_discriminant is only supported on enums"))
+    }
+    if order.groups && !matches!(input_item, Item::Enum(_)) {
+        return Err(syn::Error::new(Span::call_site(), "groups is only supported on enums"))
+    }
+    if order.fix && !matches!(input_item, Item::Enum(_)) {
+        return Err(syn::Error::new(Span::call_site(), "fix is only supported on enums"))
+    }
+    if order.meta {
+        match input_item {
+            Item::Enum(input_enum) => {
+                if let Some(variant) = input_enum.variants.iter().find(|x| !matches!(x.fields, Fields::Unit)) {
+                    return Err(syn::Error::new_spanned(&variant.fields, "#[sorted(meta)] requires unit variants"))
+                }
+                // The names are searched in byte order, which the other orderings do not follow
+                if order.case_insensitive || order.natural || order.reverse {
+                    return Err(syn::Error::new(
+                        Span::call_site(), "#[sorted(meta)] can not be combined with case_insensitive, natural or reverse, names are searched in byte order"
+                    ))
+                }
+            },
+            _ => return Err(syn::Error::new(Span::call_site(), "meta is only supported on enums")),
+        }
+    }
+    if order.id.is_some() && !matches!(input_item, Item::Enum(_)) {
+        return Err(syn::Error::new(Span::call_site(), "id is only supported on enums and match expressions"))
+    }
+
+    // Only the derive list is checked, not the content of the item
+    if order.derives {
+        return match input_item {
+            Item::Enum(ItemEnum { attrs, .. }) | Item::Struct(ItemStruct { attrs, .. }) | Item::Union(ItemUnion { attrs, .. }) => {
+                sorted_derives(attrs, order)
+            },
+            _ => Err(syn::Error::new(Span::call_site(), "derives is only supported on enums, structs and unions")),
+        }
+    }
 
-        let variants: Vec<&Ident> = input_enum.variants
+    match input_item {
+        Item::Enum(input_enum) => sorted_enum(input_enum, order),
+        Item::Struct(ItemStruct { fields: Fields::Named(fields), .. }) => {
+            let field_idents: Vec<&Ident> = fields.named
+                .iter()
+                .filter_map(|x| x.ident.as_ref())
+                .collect();
+            sorted_idents(&field_idents, order)
+        },
+        Item::Impl(input_impl) => {
+            let item_idents: Vec<&Ident> = input_impl.items
+                .iter()
+                .filter_map(impl_item_ident)
+                .collect();
+            sorted_idents(&item_idents, order)
+        },
+        Item::Mod(ItemMod { content: Some((_, items)), .. }) => {
+            let item_idents: Vec<&Ident> = items
+                .iter()
+                .filter_map(item_ident)
+                .collect();
+            sorted_idents(&item_idents, order)
+        },
+        Item::Use(input_use) => sorted_use_tree(&input_use.tree, order),
+        Item::Const(input_const) => sorted_string_array(&input_const.expr, order),
+        // Raise a compile error if applied on something that can not be sorted
+        _ => Err(syn::Error::new(Span::call_site(), ERROR_MESSAGE)),
+    }
+}
+
+fn sorted_enum(input_enum: &ItemEnum, order: &SortOrder) -> Result<(), syn::Error> {
+    let variants: Vec<&Ident> = input_enum.variants
+        .iter()
+        .map(|x| &x.ident)
+        .collect();
+    let sections: Vec<Section> = enum_sections(&input_enum.variants, order)?;
+
+    let discriminants: Vec<Discriminant> = if order.by_discriminant {
+        discriminant_values(&input_enum.variants)?
+            .into_iter()
+            .zip(&variants)
+            .map(|(value, ident)| Discriminant { ident, value })
+            .collect()
+    } else {
+        Vec::new()
+    };
+
+    // cargo sorted does not reorder variants by discriminant, nor variants whose discriminants would change
+    let rewritable: bool = !order.by_discriminant && !has_fixed_discriminants(&input_enum.attrs, &input_enum.variants);
+
+    // Each section is sorted independently
+    let errors = sections.iter().filter_map(|section| {
+        let range: Range<usize> = section.range.clone();
+        let name: Option<&str> = section.name.as_deref();
+
+        if order.by_discriminant {
+            let compare = |a: &Discriminant, b: &Discriminant| order.compare_values(a.value, b.value);
+            unsorted_section_error(&discriminants[range.clone()], &variants[range], compare, name, rewritable)
+        } else {
+            let compare = |a: &&Ident, b: &&Ident| order.compare_names(&a.to_string(), &b.to_string());
+            unsorted_section_error(&variants[range.clone()], &variants[range], compare, name, rewritable)
+        }
+    });
+
+    // Return with the full provided input if the list is sorted
+    match combine_errors(errors) {
+        None => Ok(()),
+        Some(error) => Err(error),
+    }
+}
+
+/// Sort the variants of the enum in place if requested, each section independently.
+///
+/// Refuse to reorder variants when it would change their discriminants, which the enum exposes through
+/// explicit discriminants or a #[repr].
+fn fix_enum(input_item: &mut Item, order: &SortOrder) -> Result<(), syn::Error> {
+    let Item::Enum(input_enum) = input_item else { return Ok(()) };
+    if !order.fix {
+        return Ok(())
+    }
+
+    let repr_error = input_enum.attrs
+        .iter()
+        .find(|x| x.path().is_ident("repr"))
+        .map(|x| syn::Error::new_spanned(x, "#[sorted(fix)] can not reorder the variants of a #[repr] enum"));
+    let discriminant_error = input_enum.variants
+        .iter()
+        .find_map(|x| x.discriminant.as_ref())
+        .map(|(_, expr)| syn::Error::new_spanned(expr, "#[sorted(fix)] can not reorder variants with explicit discriminants"));
+    if let Some(error) = combine_errors(repr_error.into_iter().chain(discriminant_error)) {
+        return Err(error)
+    }
+
+    let sections: Vec<Section> = enum_sections(&input_enum.variants, order)?;
+    let trailing_comma: bool = input_enum.variants.trailing_punct();
+    let mut variants: Vec<Variant> = std::mem::take(&mut input_enum.variants).into_iter().collect();
+
+    for section in sections {
+        let section_variants: &mut [Variant] = &mut variants[section.range];
+        section_variants.sort_by(|a, b| order.compare_names(&a.ident.to_string(), &b.ident.to_string()));
+
+        // The section marker stays on the first variant of the section
+        let mut markers: Vec<Attribute> = Vec::new();
+        for variant in section_variants.iter_mut() {
+            let (variant_markers, attrs) = std::mem::take(&mut variant.attrs).into_iter().partition(is_section_attribute);
+            markers.extend::<Vec<Attribute>>(variant_markers);
+            variant.attrs = attrs;
+        }
+        if let Some(first) = section_variants.first_mut() {
+            first.attrs.splice(0..0, markers);
+        }
+    }
+
+    input_enum.variants = variants.into_iter().collect();
+    if trailing_comma {
+        input_enum.variants.push_punct(Comma::default());
+    }
+
+    Ok(())
+}
+
+/// Check that every #[derive] list is sorted, comparing the last segment of each path.
+fn sorted_derives(attrs: &[Attribute], order: &SortOrder) -> Result<(), syn::Error> {
+    let derive_attributes: Vec<&Attribute> = attrs.iter().filter(|x| x.path().is_ident("derive")).collect();
+
+    if derive_attributes.is_empty() {
+        return Err(syn::Error::new(Span::call_site(), "expected #[derive] attribute"))
+    }
+
+    let mut errors: Vec<syn::Error> = Vec::new();
+    for attribute in derive_attributes {
+        let paths: Punctuated<Path, Comma> = attribute.parse_args_with(Punctuated::parse_terminated)?;
+        let names: Vec<String> = paths
             .iter()
-            .map(|x| &x.ident)
+            .map(|path| path.segments.iter().map(|x| format!("{}", x.ident)).collect::<Vec<String>>().join("::"))
             .collect();
+        let paths: Vec<&Path> = paths.iter().collect();
+
+        let last_segment = |name: &String| -> String { name.rsplit("::").next().unwrap().to_string() };
+        let compare = |a: &String, b: &String| order.compare_names(&last_segment(a), &last_segment(b));
+        errors.extend(unsorted_error(&names, &paths, compare, false));
+    }
+
+    match combine_errors(errors) {
+        None => Ok(()),
+        Some(error) => Err(error),
+    }
+}
+
+/// Check that a list of identifiers (fields, methods, items) is sorted.
+fn sorted_idents(idents: &[&Ident], order: &SortOrder) -> Result<(), syn::Error> {
+    match unsorted_error(idents, idents, |a, b| order.compare_names(&a.to_string(), &b.to_string()), false) {
+        None => Ok(()),
+        Some(error) => Err(error),
+    }
+}
+
+/// Check that every braced list of a use tree is sorted, `self` first.
+fn sorted_use_tree(tree: &UseTree, order: &SortOrder) -> Result<(), syn::Error> {
+    let mut groups: Vec<&UseGroup> = Vec::new();
+    use_groups(tree, &mut groups);
+
+    if groups.is_empty() {
+        return Err(syn::Error::new(Span::call_site(), "expected braced use list"))
+    }
+
+    let compare = |a: &String, b: &String| match (a.as_str(), b.as_str()) {
+        ("self", "self") => Ordering::Equal,
+        ("self", _) => Ordering::Less,
+        (_, "self") => Ordering::Greater,
+        (a, b) => order.compare_names(a, b),
+    };
+
+    let errors = groups.into_iter().filter_map(|group| {
+        let names: Vec<String> = group.items.iter().map(use_tree_name).collect();
+        let trees: Vec<&UseTree> = group.items.iter().collect();
+        unsorted_error(&names, &trees, compare, false)
+    });
+
+    match combine_errors(errors) {
+        None => Ok(()),
+        Some(error) => Err(error),
+    }
+}
+
+/// Check that an array or slice of string literals is sorted.
+fn sorted_string_array(expr: &Expr, order: &SortOrder) -> Result<(), syn::Error> {
+    let array: &ExprArray = match expr {
+        Expr::Array(array) => array,
+        Expr::Reference(ExprReference { expr, .. }) => match &**expr {
+            Expr::Array(array) => array,
+            _ => return Err(syn::Error::new_spanned(expr, "expected array of string literals")),
+        },
+        _ => return Err(syn::Error::new_spanned(expr, "expected array of string literals")),
+    };
+
+    let literals: Vec<&LitStr> = array.elems
+        .iter()
+        .map(|elem| match elem {
+            Expr::Lit(ExprLit { lit: Lit::Str(literal), .. }) => Ok(literal),
+            _ => Err(syn::Error::new_spanned(elem, "expected string literal")),
+        })
+        .collect::<Result<_, _>>()?;
+    let values: Vec<String> = literals.iter().map(|x| x.value()).collect();
+
+    match unsorted_error(&values, &literals, |a, b| order.compare_names(a, b), false) {
+        None => Ok(()),
+        Some(error) => Err(error),
+    }
+}
+
+/// Generate the hidden macro carrying the variants of an enum with an id, if any.
+///
+/// The matches sharing the id invoke it with their arms, to compare them with the declaration order.
+/// The macro follows the textual scoping of `macro_rules!`, the enum must be declared before the matches.
+fn variants_macro(input_item: &Item, order: &SortOrder) -> TokenStream2 {
+    let (Item::Enum(input_enum), Some(id)) = (input_item, &order.id) else {
+        return TokenStream2::new()
+    };
+
+    let macro_ident: Ident = format_ident!("__sorted_variants_{}", id);
+    let id_ident: Ident = format_ident!("{}", id);
+    let variants = input_enum.variants.iter().map(|x| &x.ident);
+
+    quote! {
+        #[allow(unused_macros)]
+        macro_rules! #macro_ident {
+            ($($arms:tt)*) => {
+                ::sorted::__check_variants! { #id_ident [#(#variants),*] $($arms)* }
+            };
+        }
+    }
+}
+
+/// Generate the name lookup methods of an enum with unit variants, if requested.
+///
+/// The names are listed in byte order, as required by the binary search of `from_name`, and ordinals are
+/// indices in that list.
+fn meta_impl(input_item: &Item, order: &SortOrder) -> TokenStream2 {
+    let Item::Enum(input_enum) = input_item else { return TokenStream2::new() };
+    if !order.meta || input_enum.variants.iter().any(|x| !matches!(x.fields, Fields::Unit)) {
+        return TokenStream2::new()
+    }
+
+    let mut variants: Vec<&Ident> = input_enum.variants.iter().map(|x| &x.ident).collect();
+    variants.sort_by_key(|x| x.to_string());
+    let names: Vec<String> = variants.iter().map(|x| x.to_string()).collect();
+    let ordinals: Vec<usize> = (0..variants.len()).collect();
+
+    let ident: &Ident = &input_enum.ident;
+    let (impl_generics, ty_generics, where_clause) = input_enum.generics.split_for_impl();
+
+    quote! {
+        impl #impl_generics #ident #ty_generics #where_clause {
+            /// Names of the variants, sorted.
+            pub const VARIANT_NAMES: &'static [&'static str] = &[#(#names),*];
 
-        // Return with the full provided input if the list is sorted
-        if variants.is_sorted() {
-            return Ok(())
+            /// Return the variant with the given name, if any.
+            pub fn from_name(name: &str) -> ::core::option::Option<Self> {
+                match Self::VARIANT_NAMES.binary_search(&name) {
+                    #(::core::result::Result::Ok(#ordinals) => ::core::option::Option::Some(Self::#variants),)*
+                    _ => ::core::option::Option::None,
+                }
+            }
+
+            /// Return the name of the variant.
+            pub fn name(&self) -> &'static str {
+                Self::VARIANT_NAMES[self.ordinal()]
+            }
+
+            /// Return the index of the variant in `VARIANT_NAMES`.
+            pub fn ordinal(&self) -> usize {
+                match *self {
+                    #(Self::#variants => #ordinals,)*
+                }
+            }
         }
+    }
+}
 
-        // Otherwise, build a correct error
-        let (unsorted_index, should_be_index): (usize, usize) = find_unsorted_element(&variants);
+/// Arguments of `__check_variants!`: the id, the enum variants, the level, and the variant of each match arm.
+struct VariantsCheck {
+    id: Ident,
+    variants: Vec<Ident>,
+    warn: bool,
+    arms: Vec<(Ident, TokenStream2)>,
+}
 
-        let unsorted_element: &Ident = &variants[unsorted_index];
-        let should_be_elmt: &Ident = &variants[should_be_index];
-        let error_message: String = format!("{} should sort before {}", unsorted_element, should_be_elmt);
+impl Parse for VariantsCheck {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let id: Ident = input.parse()?;
 
-        Err(syn::Error::new(variants[unsorted_index].span(), error_message))
+        let content;
+        syn::bracketed!(content in input);
+        let variants: Punctuated<Ident, Comma> = content.parse_terminated(Ident::parse, Comma)?;
+        let warn: LitBool = input.parse()?;
 
-    } else {
-        // Raise a compile error if applied on something that is not an enum
-        Err(syn::Error::new(Span::call_site(), ERROR_MESSAGE))
+        let arms: Punctuated<(Ident, TokenStream2), Comma> = input.parse_terminated(|arm: ParseStream| {
+            let variant: Ident = arm.parse()?;
+            let pattern;
+            syn::braced!(pattern in arm);
+            Ok((variant, pattern.parse()?))
+        }, Comma)?;
+
+        Ok(VariantsCheck { id, variants: variants.into_iter().collect(), warn: warn.value, arms: arms.into_iter().collect() })
     }
 }
 
-fn _check(mut input_item_fn: ItemFn) -> Result<TokenStream2, (TokenStream2, syn::Error)> {
+fn _check_variants(input: &VariantsCheck) -> Option<syn::Error> {
+    // Unknown variants can not be placed, report them instead of the order
+    let positions: Vec<Option<usize>> = input.arms
+        .iter()
+        .map(|(variant, _)| input.variants.iter().position(|x| x == variant))
+        .collect();
+    let unknown_errors = input.arms
+        .iter()
+        .zip(&positions)
+        .filter(|(_, position)| position.is_none())
+        .map(|((variant, pattern), _)| {
+            syn::Error::new_spanned(pattern, format!("{} is not a variant of {}", variant, input.id))
+        });
+    if let Some(error) = combine_errors(unknown_errors) {
+        return Some(error)
+    }
+
+    let positions: Vec<usize> = positions.into_iter().flatten().collect();
+    let declared: String = input.variants.iter().map(|x| x.to_string()).collect::<Vec<String>>().join(", ");
+
+    let errors = find_unsorted_elements(&positions, |a, b| a.cmp(b))
+        .into_iter()
+        .enumerate()
+        .map(|(error_index, (unsorted_index, should_be_index))| {
+            let mut error_message: String = format!(
+                "{} should sort before {}, following the order of {}",
+                input.arms[unsorted_index].0, input.arms[should_be_index].0, input.id
+            );
+            if error_index == 0 {
+                error_message.push_str(&format!("\nnote: {} declares: {}", input.id, declared));
+            }
+            syn::Error::new_spanned(&input.arms[unsorted_index].1, error_message)
+        });
+
+    combine_errors(errors)
+}
+
+/// Emit the item along with the checks generated for it, placed where they are valid wherever the item is.
+///
+/// Functions may be methods and constants may be associated constants, where only associated items are allowed,
+/// so their checks go in their body or initializer. Other items can not be associated items, their checks follow them.
+fn with_checks(input_item: &mut Item, checks: TokenStream2) -> TokenStream2 {
+    if checks.is_empty() {
+        return quote!{ #input_item }
+    }
+
+    match input_item {
+        Item::Fn(item_fn) => {
+            let block: Block = parse_quote!({ #checks });
+            item_fn.block.stmts.splice(0..0, block.stmts);
+            quote!{ #input_item }
+        },
+        Item::Const(ItemConst { expr, .. }) | Item::Static(ItemStatic { expr, .. }) => {
+            *expr = parse_quote!({ #checks #expr });
+            quote!{ #input_item }
+        },
+        _ => quote!{ #input_item #checks },
+    }
+}
+
+fn _check(mut input_item: Item) -> Result<TokenStream2, (TokenStream2, syn::Error)> {
+    // Visit every nested function, method, closure and constant expression of the item
     let mut visitor: CheckSortedMatch = CheckSortedMatch::new();
-    visitor.visit_item_fn_mut(&mut input_item_fn);
+    visitor.visit_item_mut(&mut input_item);
 
-    let result: TokenStream2 = quote!{ #input_item_fn };
+    // Matches with an id are checked by the macro generated along with their enum
+    let variants_checks: &[TokenStream2] = &visitor.variants_checks;
+    let warnings: Option<TokenStream2> = combine_errors(visitor.warnings).map(|x| report(x, true));
+    let checks: TokenStream2 = quote!{ #(#variants_checks)* #warnings };
+    let result: TokenStream2 = with_checks(&mut input_item, checks);
 
-    if visitor.errors.is_empty() {
-        Ok(result)
-    } else {
-        Err((result, visitor.errors.first().unwrap().clone()))
+    // Report every error found in the item at once
+    match combine_errors(visitor.errors) {
+        None => Ok(result),
+        Some(error) => Err((result, error)),
     }
 }
@@ -0,0 +1,154 @@
+//! Ordering options of #[sorted], shared with the cargo-sorted rewriter.
+
+use std::cmp::Ordering;
+
+use syn::meta::ParseNestedMeta;
+use syn::punctuated::Punctuated;
+use syn::token::Comma;
+use syn::{Attribute, Error, Ident, LitStr, Variant};
+
+/// How the elements of a #[sorted] item are compared.
+///
+/// Parsed from the arguments of the attribute, e.g. `#[sorted(natural, reverse)]`.
+#[derive(Clone, Default)]
+pub(crate) struct SortOrder {
+    pub(crate) case_insensitive: bool,
+    pub(crate) natural: bool,
+    pub(crate) reverse: bool,
+    pub(crate) by_discriminant: bool,
+    pub(crate) groups: bool,
+    pub(crate) allow_wildcard_anywhere: bool,
+    pub(crate) full_path: bool,
+    pub(crate) id: Option<String>,  // Name shared by an enum and the matches following its order
+    pub(crate) derives: bool,
+    pub(crate) warn: bool,  // Report unsorted elements as warnings rather than errors
+    pub(crate) fix: bool,  // Sort the variants of enums rather than reporting them
+    pub(crate) meta: bool,  // Generate the name lookup methods of enums
+}
+
+impl SortOrder {
+    /// Parse one argument of the attribute.
+    pub(crate) fn parse_meta(&mut self, meta: ParseNestedMeta) -> Result<(), Error> {
+        if meta.path.is_ident("case_insensitive") {
+            self.case_insensitive = true;
+        } else if meta.path.is_ident("natural") {
+            self.natural = true;
+        } else if meta.path.is_ident("reverse") {
+            self.reverse = true;
+        } else if meta.path.is_ident("by_discriminant") {
+            self.by_discriminant = true;
+        } else if meta.path.is_ident("groups") {
+            self.groups = true;
+        } else if meta.path.is_ident("allow_wildcard_anywhere") {
+            self.allow_wildcard_anywhere = true;
+        } else if meta.path.is_ident("full_path") {
+            self.full_path = true;
+        } else if meta.path.is_ident("meta") {
+            self.meta = true;
+        } else if meta.path.is_ident("fix") {
+            self.fix = true;
+        } else if meta.path.is_ident("warn") {
+            self.warn = true;
+        } else if meta.path.is_ident("derives") {
+            self.derives = true;
+        } else if meta.path.is_ident("id") {
+            let id: LitStr = meta.value()?.parse()?;
+            let ident: Ident = id.parse().map_err(|_| Error::new_spanned(&id, "expected identifier"))?;
+            self.id = Some(format!("{}", ident));
+        } else {
+            return Err(meta.error("unsupported #[sorted] option"))
+        }
+
+        Ok(())
+    }
+
+    /// Compare two names according to the options.
+    pub(crate) fn compare_names(&self, a: &str, b: &str) -> Ordering {
+        let ordering: Ordering = if self.natural {
+            natural_cmp(a, b, self.case_insensitive)
+        } else {
+            text_cmp(a, b, self.case_insensitive)
+        };
+
+        self.direction(ordering)
+    }
+
+    /// Compare two discriminant values according to the options.
+    pub(crate) fn compare_values(&self, a: i128, b: i128) -> Ordering {
+        self.direction(a.cmp(&b))
+    }
+
+    fn direction(&self, ordering: Ordering) -> Ordering {
+        if self.reverse { ordering.reverse() } else { ordering }
+    }
+}
+
+/// Compare two strings, ignoring ASCII case if requested.
+fn text_cmp(a: &str, b: &str, case_insensitive: bool) -> Ordering {
+    if case_insensitive {
+        a.bytes().map(|x| x.to_ascii_lowercase()).cmp(b.bytes().map(|x| x.to_ascii_lowercase()))
+    } else {
+        a.cmp(b)
+    }
+}
+
+/// Compare two strings with digit runs compared numerically, so that `Irq2` sorts before `Irq10`.
+fn natural_cmp(a: &str, b: &str, case_insensitive: bool) -> Ordering {
+    let mut a_chunks = chunks(a);
+    let mut b_chunks = chunks(b);
+
+    loop {
+        let (a_chunk, b_chunk) = match (a_chunks.next(), b_chunks.next()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(a_chunk), Some(b_chunk)) => (a_chunk, b_chunk),
+        };
+
+        let is_number = |chunk: &str| chunk.starts_with(|x: char| x.is_ascii_digit());
+        let ordering: Ordering = if is_number(a_chunk) && is_number(b_chunk) {
+            // Compare by magnitude first, so that arbitrarily long numbers do not overflow
+            let a_digits: &str = a_chunk.trim_start_matches('0');
+            let b_digits: &str = b_chunk.trim_start_matches('0');
+            a_digits.len().cmp(&b_digits.len()).then_with(|| a_digits.cmp(b_digits))
+        } else {
+            text_cmp(a_chunk, b_chunk, case_insensitive)
+        };
+
+        if ordering != Ordering::Equal {
+            return ordering
+        }
+    }
+}
+
+/// Split a string into runs of digits and runs of non digits.
+fn chunks(text: &str) -> impl Iterator<Item = &str> {
+    let mut rest: &str = text;
+
+    std::iter::from_fn(move || {
+        let first: char = rest.chars().next()?;
+        let end: usize = rest
+            .find(|x: char| x.is_ascii_digit() != first.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let (chunk, remaining) = rest.split_at(end);
+        rest = remaining;
+        Some(chunk)
+    })
+}
+
+/// Return true if the enum exposes the discriminants of its variants, through a #[repr] or explicit discriminants.
+///
+/// Reordering the variants of such an enum would change them.
+pub(crate) fn has_fixed_discriminants(attrs: &[Attribute], variants: &Punctuated<Variant, Comma>) -> bool {
+    attrs.iter().any(|x| x.path().is_ident("repr")) || variants.iter().any(|x| x.discriminant.is_some())
+}
+
+/// Return true if the attribute is a #[sorted::section] marker, starting a new group of variants.
+pub(crate) fn is_section_attribute(attribute: &Attribute) -> bool {
+    let segments: Vec<String> = attribute.path().segments
+        .iter()
+        .map(|x| format!("{}", x.ident))
+        .collect();
+
+    segments == ["sorted", "section"]
+}
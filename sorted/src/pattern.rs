@@ -0,0 +1,359 @@
+//! Ordering of match arm patterns, shared with the cargo-sorted rewriter.
+
+use std::cmp::Ordering;
+use std::fmt::{self, Display};
+
+use proc_macro2::TokenStream;
+use quote::ToTokens;
+use syn::{Error, Expr, ExprLit, Lit, Pat, Path, RangeLimits};
+
+use crate::order::SortOrder;
+
+/// The value a pattern is sorted by.
+pub(crate) enum PatternKey {
+    Bool(bool),
+    Number(f64, i128),  // Floats are compared by their approximation, integers exactly
+    Char(char),
+    Str(String),
+    ByteStr(Vec<u8>),
+    Range(Box<PatternKey>, Box<PatternKey>, bool, bool),  // Start, end, whether the end is included and the bounds are known
+    Path(String, Vec<PatternKey>),  // Joined path, along with the keys of the nested patterns
+    Or(Vec<PatternKey>),  // Alternatives, ranked by the first one
+    Binding,  // Bare identifier binding the value, catches everything like the wildcard
+    Wild,
+}
+
+/// The key of a match arm, along with how it is displayed and where it is reported.
+pub(crate) struct ArmKey {
+    pub(crate) display: String,
+    pub(crate) key: PatternKey,
+    pub(crate) tokens: TokenStream,
+}
+
+impl Display for ArmKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.display)
+    }
+}
+
+impl PatternKey {
+    /// Rank of the kind of pattern, used when comparing different kinds of patterns.
+    fn rank(&self) -> u8 {
+        match self {
+            PatternKey::Bool(_) => 0,
+            PatternKey::Number(..) => 1,
+            PatternKey::Char(_) => 2,
+            PatternKey::Str(_) => 3,
+            PatternKey::ByteStr(_) => 4,
+            PatternKey::Range(start, ..) => start.rank(),
+            PatternKey::Path(..) => 5,
+            PatternKey::Or(keys) => keys[0].rank(),
+            PatternKey::Binding => 6,
+            PatternKey::Wild => 7,
+        }
+    }
+
+    /// Compare two keys according to the options.
+    ///
+    /// Catch-all patterns always sort last, regardless of the direction.
+    pub(crate) fn compare<'a>(&'a self, other: &'a PatternKey, order: &SortOrder) -> Ordering {
+        match (self, other) {
+            // Ranges are sorted by their start, among the values of its kind
+            (PatternKey::Range(start, ..), other) => start.compare(other, order),
+            (key, PatternKey::Range(start, ..)) => key.compare(start, order),
+            (PatternKey::Or(keys), other) => keys[0].compare(other, order),
+            (key, PatternKey::Or(keys)) => key.compare(&keys[0], order),
+            (PatternKey::Bool(a), PatternKey::Bool(b)) => order.compare_values(*a as i128, *b as i128),
+            (PatternKey::Number(a_float, a_int), PatternKey::Number(b_float, b_int)) => {
+                let ordering: Ordering = a_float.partial_cmp(b_float).unwrap_or(Ordering::Equal);
+                if ordering == Ordering::Equal {
+                    order.compare_values(*a_int, *b_int)
+                } else if order.reverse {
+                    ordering.reverse()
+                } else {
+                    ordering
+                }
+            },
+            (PatternKey::Char(a), PatternKey::Char(b)) => order.compare_values(*a as i128, *b as i128),
+            (PatternKey::Str(a), PatternKey::Str(b)) => order.compare_names(a, b),
+            (PatternKey::ByteStr(a), PatternKey::ByteStr(b)) => {
+                let ordering: Ordering = a.cmp(b);
+                if order.reverse { ordering.reverse() } else { ordering }
+            },
+            (PatternKey::Path(a_path, a_nested), PatternKey::Path(b_path, b_nested)) => {
+                // Paths are compared by their last segment, unless the full path is requested
+                let name = |path: &'a str| -> &'a str {
+                    if order.full_path { path } else { path.rsplit("::").next().unwrap() }
+                };
+
+                order.compare_names(name(a_path), name(b_path)).then_with(|| {
+                    a_nested
+                        .iter()
+                        .zip(b_nested)
+                        .map(|(a, b)| a.compare(b, order))
+                        .find(|x| *x != Ordering::Equal)
+                        .unwrap_or_else(|| a_nested.len().cmp(&b_nested.len()))
+                })
+            },
+            (a, b) => a.rank().cmp(&b.rank()),
+        }
+    }
+
+    /// Return true if the key matches every value.
+    pub(crate) fn is_catch_all(&self) -> bool {
+        match self {
+            PatternKey::Or(keys) => keys[0].is_catch_all(),
+            key => matches!(key, PatternKey::Binding | PatternKey::Wild),
+        }
+    }
+
+    /// Return true if both keys can match the same value.
+    ///
+    /// If `possibly` is set, catch-all patterns, nested or not, and ranges bounded by constants of unknown value
+    /// overlap everything they may match. Otherwise they never do, since catch-all patterns are sorted last.
+    pub(crate) fn overlaps(&self, other: &PatternKey, possibly: bool) -> bool {
+        match (self, other) {
+            (PatternKey::Or(keys), other) => keys.iter().any(|x| x.overlaps(other, possibly)),
+            (key, PatternKey::Or(keys)) => keys.iter().any(|x| key.overlaps(x, possibly)),
+            (a, b) if a.is_catch_all() || b.is_catch_all() => possibly,
+            (PatternKey::Range(.., false), _) | (_, PatternKey::Range(.., false)) if !possibly => false,
+            (PatternKey::Path(a_path, a_nested), PatternKey::Path(b_path, b_nested)) => {
+                a_path.rsplit("::").next() == b_path.rsplit("::").next()
+                    && a_nested.iter().zip(b_nested).all(|(a, b)| a.overlaps(b, possibly))
+            },
+            (a, b) => {
+                // Values are compared in ascending order, whatever the options
+                let ascending: SortOrder = SortOrder::default();
+                let ends_before = |end: &PatternKey, inclusive: bool, start: &PatternKey| match end.compare(start, &ascending) {
+                    Ordering::Less => true,
+                    Ordering::Equal => !inclusive,
+                    Ordering::Greater => false,
+                };
+
+                let (a_start, a_end, a_inclusive) = a.bounds();
+                let (b_start, b_end, b_inclusive) = b.bounds();
+                a_start.rank() == b_start.rank()
+                    && !ends_before(a_end, a_inclusive, b_start)
+                    && !ends_before(b_end, b_inclusive, a_start)
+            },
+        }
+    }
+
+    /// Return the first and last values matched by the key, and whether the last one is included.
+    fn bounds(&self) -> (&PatternKey, &PatternKey, bool) {
+        match self {
+            PatternKey::Range(start, end, inclusive, _) => (start, end, *inclusive),
+            key => (key, key, true),
+        }
+    }
+
+    /// Return the lowest or highest value of the kind of the key.
+    fn extreme(&self, highest: bool) -> PatternKey {
+        match (self, highest) {
+            (PatternKey::Char(_), false) => PatternKey::Char(char::MIN),
+            (PatternKey::Char(_), true) => PatternKey::Char(char::MAX),
+            (_, false) => PatternKey::Number(f64::MIN, i128::MIN),
+            (_, true) => PatternKey::Number(f64::MAX, i128::MAX),
+        }
+    }
+}
+
+/// Compute the key of a match arm pattern.
+///
+/// Or-patterns are ranked by their first alternative, and their alternatives must be sorted too, or follow the
+/// order of the enum with an id.
+/// Return an error if the pattern can not be compared.
+pub(crate) fn arm_key(pat: &Pat, order: &SortOrder, errors: &mut Vec<Error>) -> Result<ArmKey, Error> {
+    match pat {
+        Pat::Or(pat_or) => {
+            let cases: Vec<ArmKey> = pat_or.cases
+                .iter()
+                .map(|x| arm_key(x, order, errors))
+                .collect::<Result<_, _>>()?;
+
+            // With an id, alternatives follow the order of the enum instead, like the arms
+            let unsorted_case: Option<(usize, &ArmKey)> = cases.windows(2).enumerate().find_map(|(index, pair)| {
+                (pair[0].key.compare(&pair[1].key, order) == Ordering::Greater).then_some((index + 1, &pair[0]))
+            });
+            if let (Some((index, should_be)), None) = (unsorted_case, &order.id) {
+                errors.push(Error::new_spanned(
+                    &cases[index].tokens, format!("{} should sort before {}", cases[index], should_be)
+                ));
+            }
+
+            let display: String = cases.iter().map(|x| x.display.as_str()).collect::<Vec<&str>>().join(" | ");
+            let tokens: TokenStream = cases[0].tokens.clone();
+            let key: PatternKey = PatternKey::Or(cases.into_iter().map(|x| x.key).collect());
+
+            Ok(ArmKey { display, key, tokens })
+        },
+        Pat::Range(range) => {
+            // Open bounds, and constants other than MIN and MAX, are taken as the extreme values of the other bound
+            let start: Option<PatternKey> = range.start.as_deref().and_then(bound_key);
+            let end: Option<PatternKey> = range.end.as_deref().and_then(bound_key);
+            let known: bool = range.start.is_none() == start.is_none() && range.end.is_none() == end.is_none();
+            let (start, end): (PatternKey, PatternKey) = match (start, end) {
+                (Some(start), Some(end)) => (start, end),
+                (Some(start), None) => {
+                    let end: PatternKey = start.extreme(true);
+                    (start, end)
+                },
+                (None, Some(end)) => (end.extreme(false), end),
+                (None, None) => (PatternKey::Number(f64::MIN, i128::MIN), PatternKey::Number(f64::MAX, i128::MAX)),
+            };
+
+            let bound = |bound: &Option<Box<Expr>>| bound.as_deref().map_or(String::new(), bound_display);
+            let display: String = format!(
+                "{}{}{}", bound(&range.start), range.limits.to_token_stream(), bound(&range.end)
+            );
+            let inclusive: bool = matches!(range.limits, RangeLimits::Closed(_)) || range.end.is_none();
+            let key: PatternKey = PatternKey::Range(Box::new(start), Box::new(end), inclusive, known);
+            Ok(ArmKey { display, key, tokens: pat.to_token_stream() })
+        },
+        Pat::Lit(lit) => {
+            let key: PatternKey = lit_key(&lit.lit).ok_or_else(|| unsupported(pat))?;
+            Ok(ArmKey { display: lit.to_token_stream().to_string(), key, tokens: lit.to_token_stream() })
+        },
+        Pat::Paren(paren) => arm_key(&paren.pat, order, errors),
+        Pat::Reference(reference) => arm_key(&reference.pat, order, errors),
+        Pat::Ident(ident) => {
+            if let Some((_, subpat)) = &ident.subpat {
+                return arm_key(subpat, order, errors)
+            }
+
+            // Lowercase identifiers bind the value, others are constants or unit variants
+            let name: String = format!("{}", ident.ident);
+            let key: PatternKey = if name.starts_with(|x: char| x.is_lowercase() || x == '_') {
+                PatternKey::Binding
+            } else {
+                PatternKey::Path(name.clone(), Vec::new())
+            };
+            Ok(ArmKey { display: name, key, tokens: ident.to_token_stream() })
+        },
+        Pat::Path(path) => {
+            let name: String = path_name(&path.path);
+            Ok(ArmKey { display: name.clone(), key: PatternKey::Path(name, Vec::new()), tokens: path.path.to_token_stream() })
+        },
+        Pat::Struct(struc) => {
+            let name: String = path_name(&struc.path);
+            let nested: Vec<ArmKey> = struc.fields
+                .iter()
+                .map(|x| nested_key(&x.pat, order, errors))
+                .collect();
+            Ok(path_key(name, nested, struc.path.to_token_stream(), pat.to_token_stream()))
+        },
+        Pat::TupleStruct(tuple_struct) => {
+            let name: String = path_name(&tuple_struct.path);
+            let nested: Vec<ArmKey> = tuple_struct.elems
+                .iter()
+                .filter(|x| !matches!(x, Pat::Rest(_)))
+                .map(|x| nested_key(x, order, errors))
+                .collect();
+            Ok(path_key(name, nested, tuple_struct.path.to_token_stream(), pat.to_token_stream()))
+        },
+        Pat::Wild(wild) => {
+            Ok(ArmKey { display: String::from("_"), key: PatternKey::Wild, tokens: wild.underscore_token.to_token_stream() })
+        },
+        _ => Err(unsupported(pat)),
+    }
+}
+
+/// Compute the key of a pattern nested in a path pattern.
+///
+/// Nested patterns which can not be compared, like tuples or slices, catch everything for the ordering.
+fn nested_key(pat: &Pat, order: &SortOrder, errors: &mut Vec<Error>) -> ArmKey {
+    arm_key(pat, order, errors).unwrap_or_else(|_| ArmKey {
+        display: pat.to_token_stream().to_string(),
+        key: PatternKey::Wild,
+        tokens: pat.to_token_stream(),
+    })
+}
+
+fn unsupported(pat: &Pat) -> Error {
+    Error::new_spanned(pat.to_token_stream(), "unsupported by #[sorted]")
+}
+
+fn path_name(path: &Path) -> String {
+    let parts: Vec<String> = path.segments
+        .iter()
+        .map(|x| format!("{}", x.ident)).collect();
+    parts.join("::")
+}
+
+/// Build the key of a path with nested patterns.
+///
+/// Nested patterns only show up in messages when they do more than binding the fields, and in the reported span
+/// when they take part in the ordering.
+fn path_key(name: String, nested: Vec<ArmKey>, path_tokens: TokenStream, pat_tokens: TokenStream) -> ArmKey {
+    let display: String = if nested.iter().all(|x| matches!(x.key, PatternKey::Binding)) {
+        name.clone()
+    } else {
+        let parts: Vec<&str> = nested.iter().map(|x| x.display.as_str()).collect();
+        format!("{}({})", name, parts.join(", "))
+    };
+    let tokens: TokenStream = if nested.iter().all(|x| x.key.is_catch_all()) { path_tokens } else { pat_tokens };
+    let key: PatternKey = PatternKey::Path(name, nested.into_iter().map(|x| x.key).collect());
+
+    ArmKey { display, key, tokens }
+}
+
+/// Compute the key of a range bound, if its value is known.
+fn bound_key(expr: &Expr) -> Option<PatternKey> {
+    match expr {
+        Expr::Lit(ExprLit { lit, .. }) => lit_key(lit).filter(|x| matches!(x, PatternKey::Number(..) | PatternKey::Char(_))),
+        Expr::Path(path) => {
+            // Only the bounds of primitive types are known, like `i32::MIN` or `std::u8::MAX`
+            let name: String = path_name(&path.path);
+            let mut segments = name.rsplit("::");
+            let (constant, ty) = (segments.next()?, segments.next()?);
+
+            let (min, max): (i128, i128) = match ty {
+                "i8" => (i8::MIN.into(), i8::MAX.into()),
+                "i16" => (i16::MIN.into(), i16::MAX.into()),
+                "i32" => (i32::MIN.into(), i32::MAX.into()),
+                "i64" | "isize" => (i64::MIN.into(), i64::MAX.into()),
+                "i128" => (i128::MIN, i128::MAX),
+                "u8" => (u8::MIN.into(), u8::MAX.into()),
+                "u16" => (u16::MIN.into(), u16::MAX.into()),
+                "u32" => (u32::MIN.into(), u32::MAX.into()),
+                "u64" | "usize" => (u64::MIN.into(), u64::MAX.into()),
+                "u128" => (0, i128::MAX),  // u128::MAX does not fit, it is ranked as the highest value
+                "char" if constant == "MIN" => return Some(PatternKey::Char(char::MIN)),
+                "char" if constant == "MAX" => return Some(PatternKey::Char(char::MAX)),
+                _ => return None,
+            };
+
+            let value: i128 = match constant {
+                "MIN" => min,
+                "MAX" => max,
+                _ => return None,
+            };
+            Some(PatternKey::Number(value as f64, value))
+        },
+        _ => None,
+    }
+}
+
+/// Display a range bound, with paths written like in the source.
+fn bound_display(expr: &Expr) -> String {
+    match expr {
+        Expr::Path(path) => path_name(&path.path),
+        expr => expr.to_token_stream().to_string(),
+    }
+}
+
+fn lit_key(lit: &Lit) -> Option<PatternKey> {
+    match lit {
+        Lit::Bool(lit_bool) => Some(PatternKey::Bool(lit_bool.value)),
+        Lit::Int(lit_int) => {
+            let value: i128 = lit_int.base10_parse().ok()?;
+            Some(PatternKey::Number(value as f64, value))
+        },
+        Lit::Float(lit_float) => Some(PatternKey::Number(lit_float.base10_parse().ok()?, 0)),
+        Lit::Byte(lit_byte) => Some(PatternKey::Number(lit_byte.value() as f64, lit_byte.value() as i128)),
+        Lit::Char(lit_char) => Some(PatternKey::Char(lit_char.value())),
+        Lit::Str(lit_str) => Some(PatternKey::Str(lit_str.value())),
+        Lit::ByteStr(lit_byte_str) => Some(PatternKey::ByteStr(lit_byte_str.value())),
+        _ => None,
+    }
+}
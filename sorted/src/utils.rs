@@ -1,30 +1,247 @@
-use syn::{ExprMatch, Meta};
+use std::cmp::Ordering;
+use std::fmt::{self, Display};
+use std::ops::Range;
 
-pub(crate) fn find_unsorted_element<T: PartialOrd>(array: &[T]) -> (usize, usize) {
-    let length: usize = array.len();
+use proc_macro2::{Ident, TokenStream, TokenTree};
+use quote::{quote, quote_spanned, ToTokens};
+use syn::punctuated::Punctuated;
+use syn::token::Comma;
+use syn::{Attribute, Error, Expr, ExprLit, ExprMatch, ExprUnary, ImplItem, Item, Lit, LitStr, Meta, MetaList, UnOp};
+use syn::{UseGroup, UseTree, Variant};
 
-    let mut unsorted_elmt_index: usize = 0;  // First unsorted element of the array
-    let mut target_elmt_index: usize = 0;  // Position where the unsorted element should be
+use crate::order::{is_section_attribute, SortOrder};
 
-    // Find which element is not at the right place
-    for i in 1..length {
-        if array[i-1] > array[i] {
-            unsorted_elmt_index = i;
-            break;
+/// A variant identifier along with its discriminant value, displayed as the identifier.
+pub(crate) struct Discriminant<'a> {
+    pub(crate) ident: &'a Ident,
+    pub(crate) value: i128,
+}
+
+impl Display for Discriminant<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.ident)
+    }
+}
+
+/// Compute the discriminant value of each variant.
+///
+/// Implicit discriminants follow the previous one, starting at zero like the compiler does.
+pub(crate) fn discriminant_values(variants: &Punctuated<Variant, Comma>) -> Result<Vec<i128>, Error> {
+    let mut values: Vec<i128> = Vec::new();
+    let mut next_value: i128 = 0;
+
+    for variant in variants {
+        let value: i128 = match &variant.discriminant {
+            None => next_value,
+            Some((_, expr)) => integer_value(expr).ok_or_else(|| Error::new_spanned(
+                expr, "#[sorted(by_discriminant)] requires integer literal discriminants"
+            ))?,
+        };
+
+        values.push(value);
+        next_value = value + 1;
+    }
+
+    Ok(values)
+}
+
+/// Return the value of an integer literal, possibly negated.
+fn integer_value(expr: &Expr) -> Option<i128> {
+    match expr {
+        Expr::Lit(ExprLit { lit: Lit::Int(int), .. }) => int.base10_parse().ok(),
+        Expr::Unary(ExprUnary { op: UnOp::Neg(_), expr, .. }) => integer_value(expr).map(|x: i128| -x),
+        Expr::Paren(paren) => integer_value(&paren.expr),
+        _ => None,
+    }
+}
+
+/// Find every element of the array that is not at its sorted place.
+///
+/// Return a list of (unsorted element index, index of the element it should sort before).
+/// An element is considered unsorted when a previous element is greater than it.
+pub(crate) fn find_unsorted_elements<T, F>(array: &[T], compare: F) -> Vec<(usize, usize)>
+where
+    F: Fn(&T, &T) -> Ordering,
+{
+    let mut unsorted_elements: Vec<(usize, usize)> = Vec::new();
+    let mut max_elmt_index: usize = 0;  // Greatest element seen so far
+
+    for i in 1..array.len() {
+        if compare(&array[max_elmt_index], &array[i]) != Ordering::Greater {
+            max_elmt_index = i;
+            continue;
+        }
+
+        // Determine where it should be: before the first greater element
+        let target_elmt_index: usize = array[..i]
+            .iter()
+            .position(|elmt| compare(elmt, &array[i]) == Ordering::Greater)
+            .unwrap_or(max_elmt_index);
+
+        unsorted_elements.push((i, target_elmt_index));
+    }
+
+    unsorted_elements
+}
+
+/// Build one "X should sort before Y" error per unsorted element, combined into a single error.
+///
+/// The first error carries a note with the fully sorted list, so that the whole order can be fixed at once, and
+/// a help pointing at `cargo sorted` if it can reorder the elements, which is only the case of enums and matches.
+/// Return None if the names are sorted.
+pub(crate) fn unsorted_error<T, S, F>(names: &[T], spans: &[S], compare: F, rewritable: bool) -> Option<Error>
+where
+    T: Display,
+    S: ToTokens,
+    F: Fn(&T, &T) -> Ordering,
+{
+    unsorted_section_error(names, spans, compare, None, rewritable)
+}
+
+/// Same as `unsorted_error`, naming the section the names belong to in the messages.
+pub(crate) fn unsorted_section_error<T, S, F>(
+    names: &[T],
+    spans: &[S],
+    compare: F,
+    section: Option<&str>,
+    rewritable: bool,
+) -> Option<Error>
+where
+    T: Display,
+    S: ToTokens,
+    F: Fn(&T, &T) -> Ordering,
+{
+    let unsorted_elements: Vec<(usize, usize)> = find_unsorted_elements(names, &compare);
+
+    if unsorted_elements.is_empty() {
+        return None
+    }
+
+    let mut sorted_names: Vec<&T> = names.iter().collect();
+    sorted_names.sort_by(|a, b| compare(a, b));
+    let sorted_note: String = sorted_names
+        .iter()
+        .map(|name| name.to_string())
+        .collect::<Vec<String>>()
+        .join(", ");
+
+    let errors = unsorted_elements
+        .into_iter()
+        .enumerate()
+        .map(|(error_index, (unsorted_index, should_be_index))| {
+            let mut error_message: String = format!(
+                "{} should sort before {}", names[unsorted_index], names[should_be_index]
+            );
+            if let Some(section) = section {
+                error_message.push_str(&format!(" in section {}", section));
+            }
+            if error_index == 0 {
+                error_message.push_str(&format!("\nnote: sorted order is: {}", sorted_note));
+                if rewritable {
+                    error_message.push_str("\nhelp: run `cargo sorted` to reorder the source in place");
+                }
+            }
+            Error::new_spanned(&spans[unsorted_index], error_message)
+        });
+
+    combine_errors(errors)
+}
+
+/// Combine errors into a single one, reporting all of them.
+///
+/// Return None if there is no error.
+pub(crate) fn combine_errors(errors: impl IntoIterator<Item = Error>) -> Option<Error> {
+    errors.into_iter().reduce(|mut combined, error| {
+        combined.combine(error);
+        combined
+    })
+}
+
+/// Default level of the checks, from the environment of the build.
+///
+/// Read when this crate is compiled, so that cargo rebuilds it when the variable changes, which expands the macros
+/// again in every crate using them. Nothing needs to be emitted in the expansions to track it.
+const SORTED_LEVEL: Option<&str> = option_env!("SORTED_LEVEL");
+
+/// Return true if unsorted elements are reported as warnings by default, with `SORTED_LEVEL=warn`.
+pub(crate) fn warn_by_default() -> bool {
+    SORTED_LEVEL == Some("warn")
+}
+
+/// Turn the error into a compile error, or into warnings if requested.
+///
+/// Proc macros can not emit warnings on stable, so each one is the use of a deprecated item at the location of
+/// the error, with the message as deprecation note.
+pub(crate) fn report(error: Error, warn: bool) -> TokenStream {
+    if !warn {
+        return error.to_compile_error()
+    }
+
+    let warnings = error.into_iter().map(|error| {
+        let message: String = error.to_string();
+        quote_spanned! {error.span()=>
+            const _: () = {
+                #[deprecated(note = #message)]
+                struct UnsortedWarning;
+                let _ = UnsortedWarning;
+            };
         }
+    });
+
+    quote! { #(#warnings)* }
+}
+
+/// A group of consecutive variants, sorted independently of the others.
+pub(crate) struct Section {
+    pub(crate) name: Option<String>,  // Only named when grouping is enabled
+    pub(crate) range: Range<usize>,
+}
+
+/// Split the variants into the sections started by #[sorted::section] markers.
+///
+/// Without grouping, every variant belongs to a single unnamed section and markers are rejected.
+pub(crate) fn enum_sections(variants: &Punctuated<Variant, Comma>, order: &SortOrder) -> Result<Vec<Section>, Error> {
+    if !order.groups {
+        if let Some(marker) = variants.iter().flat_map(|x| &x.attrs).find(|x| is_section_attribute(x)) {
+            return Err(Error::new_spanned(marker, "#[sorted::section] requires #[sorted(groups)]"))
+        }
+        return Ok(vec![Section { name: None, range: 0..variants.len() }])
     }
 
-    let unsorted_elmt: &T = &array[unsorted_elmt_index];
+    let mut sections: Vec<Section> = Vec::new();
 
-    // Determine where it should be
-    for j in 0..length {
-        if &array[j] > unsorted_elmt {
-            target_elmt_index = j;
-            break;
+    for (index, variant) in variants.iter().enumerate() {
+        let marker: Option<&Attribute> = variant.attrs.iter().find(|x| is_section_attribute(x));
+
+        if marker.is_none() && index != 0 {
+            continue
         }
+
+        // Sections are named after their marker, or after their first variant
+        let name: String = match marker.map(|x| &x.meta) {
+            Some(Meta::List(_)) => format!("{:?}", marker.unwrap().parse_args::<LitStr>()?.value()),
+            Some(Meta::NameValue(_)) => return Err(Error::new_spanned(
+                marker, "expected #[sorted::section] or #[sorted::section(\"name\")]"
+            )),
+            Some(Meta::Path(_)) | None => format!("starting at {}", variant.ident),
+        };
+
+        if let Some(previous) = sections.last_mut() {
+            previous.range.end = index;
+        }
+        sections.push(Section { name: Some(name), range: index..variants.len() });
     }
 
-    (unsorted_elmt_index, target_elmt_index)
+    Ok(sections)
+}
+
+/// Remove the #[sorted::section] markers, which are not real attributes.
+pub(crate) fn remove_section_attributes(item: &mut Item) {
+    if let Item::Enum(item_enum) = item {
+        for variant in item_enum.variants.iter_mut() {
+            variant.attrs.retain(|x| !is_section_attribute(x));
+        }
+    }
 }
 
 /// Return Some(idx) with idx being the index of the sorted attribute.
@@ -36,7 +253,7 @@ pub(crate) fn has_sorted_attribute(expr_match: &ExprMatch) -> Option<usize> {
 
     // Find an attribute with identifier "sorted"
     for (attr_index, attribute) in expr_match.attrs.iter().enumerate() {
-        if let Meta::Path(path, ..) = &attribute.meta {
+        if let Meta::Path(path, ..) | Meta::List(MetaList { path, .. }) = &attribute.meta {
             if let Some(path_segment) = path.segments.first() {
                 if path_segment.ident == "sorted" {
                     return Some(attr_index)
@@ -47,3 +264,103 @@ pub(crate) fn has_sorted_attribute(expr_match: &ExprMatch) -> Option<usize> {
 
     None
 }
+
+/// Return the identifier of an item, if it has one.
+pub(crate) fn item_ident(item: &Item) -> Option<&Ident> {
+    match item {
+        Item::Const(item) => Some(&item.ident),
+        Item::Enum(item) => Some(&item.ident),
+        Item::ExternCrate(item) => Some(&item.ident),
+        Item::Fn(item) => Some(&item.sig.ident),
+        Item::Macro(item) => item.ident.as_ref(),
+        Item::Mod(item) => Some(&item.ident),
+        Item::Static(item) => Some(&item.ident),
+        Item::Struct(item) => Some(&item.ident),
+        Item::Trait(item) => Some(&item.ident),
+        Item::TraitAlias(item) => Some(&item.ident),
+        Item::Type(item) => Some(&item.ident),
+        Item::Union(item) => Some(&item.ident),
+        _ => None,
+    }
+}
+
+/// Return the identifier of an impl block item, if it has one.
+pub(crate) fn impl_item_ident(item: &ImplItem) -> Option<&Ident> {
+    match item {
+        ImplItem::Const(item) => Some(&item.ident),
+        ImplItem::Fn(item) => Some(&item.sig.ident),
+        ImplItem::Type(item) => Some(&item.ident),
+        _ => None,
+    }
+}
+
+/// Push every braced list of the use tree, outermost first.
+pub(crate) fn use_groups<'a>(tree: &'a UseTree, groups: &mut Vec<&'a UseGroup>) {
+    match tree {
+        UseTree::Path(path) => use_groups(&path.tree, groups),
+        UseTree::Group(group) => {
+            groups.push(group);
+            for item in &group.items {
+                use_groups(item, groups);
+            }
+        },
+        UseTree::Name(_) | UseTree::Rename(_) | UseTree::Glob(_) => {},
+    }
+}
+
+/// Return the name a use tree is sorted by: its first identifier.
+pub(crate) fn use_tree_name(tree: &UseTree) -> String {
+    match tree {
+        UseTree::Path(path) => format!("{}", path.ident),
+        UseTree::Name(name) => format!("{}", name.ident),
+        UseTree::Rename(rename) => format!("{}", rename.ident),
+        UseTree::Glob(_) => String::from("*"),
+        UseTree::Group(group) => group.to_token_stream().to_string(),
+    }
+}
+
+/// Return the tokens listing the elements of a `check_list!` invocation.
+///
+/// A single macro invocation, like `table! { ... }`, lists the elements of its body.
+/// Anything else is a list by itself.
+pub(crate) fn list_tokens(tokens: TokenStream) -> TokenStream {
+    let trees: Vec<TokenTree> = tokens.clone().into_iter().collect();
+    let bang: Option<usize> = trees.iter().position(|x| matches!(x, TokenTree::Punct(punct) if punct.as_char() == '!'));
+
+    if let Some(bang) = bang {
+        let is_path: bool = bang != 0 && trees[..bang].iter().all(|x| match x {
+            TokenTree::Ident(_) => true,
+            TokenTree::Punct(punct) => punct.as_char() == ':',
+            _ => false,
+        });
+        let is_last: bool = match &trees[bang + 1..] {
+            [TokenTree::Group(_)] => true,
+            [TokenTree::Group(_), TokenTree::Punct(punct)] => punct.as_char() == ';',
+            _ => false,
+        };
+
+        if let (true, true, TokenTree::Group(group)) = (is_path, is_last, &trees[bang + 1]) {
+            return group.stream()
+        }
+    }
+
+    tokens
+}
+
+/// Split tokens on their top-level commas, ignoring a trailing comma.
+pub(crate) fn split_list(tokens: TokenStream) -> Vec<TokenStream> {
+    let mut elements: Vec<TokenStream> = vec![TokenStream::new()];
+
+    for tree in tokens {
+        match tree {
+            TokenTree::Punct(punct) if punct.as_char() == ',' => elements.push(TokenStream::new()),
+            tree => elements.last_mut().unwrap().extend([tree]),
+        }
+    }
+
+    if elements.last().unwrap().is_empty() {
+        elements.pop();
+    }
+
+    elements
+}
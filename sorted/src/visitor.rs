@@ -1,18 +1,71 @@
-use proc_macro2::{TokenStream};
-use quote::ToTokens;
-use syn::{visit_mut, Error, ExprMatch, Pat};
+use proc_macro2::{Delimiter, Group, Ident, Span, TokenStream, TokenTree};
+use quote::{format_ident, quote, ToTokens};
+use syn::parse::{Parse, ParseStream};
+use syn::{visit_mut, Error, Expr, ExprMatch, Macro, Meta, Pat, Token};
 use syn::visit_mut::VisitMut;
 
-use crate::utils::{find_unsorted_element, has_sorted_attribute};
+use crate::order::SortOrder;
+use crate::pattern::{arm_key, ArmKey, PatternKey};
+use crate::utils::{has_sorted_attribute, unsorted_error, warn_by_default};
+
+/// Standard macros whose arguments are expressions, separated by commas or a semicolon like `vec![0; n]`.
+const EXPRESSION_MACROS: &[&str] = &[
+    "assert", "assert_eq", "assert_ne", "dbg", "debug_assert", "debug_assert_eq", "debug_assert_ne", "eprint",
+    "eprintln", "format", "format_args", "panic", "print", "println", "todo", "unimplemented", "unreachable", "vec",
+    "write", "writeln",
+];
+
+/// Arguments of a macro from EXPRESSION_MACROS, along with their separators.
+struct MacroArgs {
+    exprs: Vec<Expr>,
+    separators: Vec<TokenStream>,
+}
+
+impl Parse for MacroArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut exprs: Vec<Expr> = Vec::new();
+        let mut separators: Vec<TokenStream> = Vec::new();
+
+        while !input.is_empty() {
+            exprs.push(input.parse()?);
+            if input.is_empty() {
+                break
+            }
+
+            if input.peek(Token![;]) {
+                separators.push(input.parse::<Token![;]>()?.to_token_stream());
+            } else {
+                separators.push(input.parse::<Token![,]>()?.to_token_stream());
+            }
+        }
+
+        Ok(MacroArgs { exprs, separators })
+    }
+}
+
+impl ToTokens for MacroArgs {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        for (index, expr) in self.exprs.iter().enumerate() {
+            expr.to_tokens(tokens);
+            if let Some(separator) = self.separators.get(index) {
+                separator.to_tokens(tokens);
+            }
+        }
+    }
+}
 
 pub(crate) struct CheckSortedMatch{
     pub(crate) errors: Vec<Error>,
+    pub(crate) warnings: Vec<Error>,  // Errors of the matches reported as warnings
+    pub(crate) variants_checks: Vec<TokenStream>,  // Invocations of the macros generated along with enums with an id
 }
 
 impl CheckSortedMatch {
     pub fn new() -> Self {
         CheckSortedMatch {
             errors: Vec::new(),
+            warnings: Vec::new(),
+            variants_checks: Vec::new(),
         }
     }
 }
@@ -24,58 +77,82 @@ impl VisitMut for CheckSortedMatch {
 
         if let Some(sorted_attribute_index) = sorted_attribute_idx_opt {
 
-            // For each arm, build a tuple:
-            //  - String; use to determine if the match is sorted
-            //  - TokenStream; token stream of the path, since path.span() does not work as expected
-            let pattern_idents: Vec<(String, TokenStream)> = i.arms
+            // Parse the ordering options, if any
+            let mut order: SortOrder = SortOrder::default();
+            if let Meta::List(_) = &i.attrs[sorted_attribute_index].meta {
+                if let Err(error) = i.attrs[sorted_attribute_index].parse_nested_meta(|meta| order.parse_meta(meta)) {
+                    self.errors.push(error);
+                }
+            }
+            if order.by_discriminant {
+                self.errors.push(syn::Error::new_spanned(
+                    &i.attrs[sorted_attribute_index], "by_discriminant is only supported on enums"
+                ));
+            }
+            if order.fix {
+                self.errors.push(syn::Error::new_spanned(
+                    &i.attrs[sorted_attribute_index], "fix is only supported on enums"
+                ));
+            }
+            let warn: bool = order.warn || warn_by_default();
+            let first_error: usize = self.errors.len();
+
+            // Compute the key of each arm, stopping at the first pattern which can not be compared
+            let arm_keys: Result<Vec<ArmKey>, Error> = i.arms
                 .iter()
-                .filter_map(|x| {
-                    match &x.pat {
-                        Pat::Ident(ident) => {
-                            Some((format!("{}", ident.ident), ident.to_token_stream()))
-                        },
-                        Pat::Path(path) => {
-                            let parts: Vec<String> = path.path.segments
-                                .iter()
-                                .map(|x| format!("{}", x.ident)).collect();
-                            Some((parts.join("::"), path.path.to_token_stream()))
-                        },
-                        Pat::Struct(struc) => {
-                            let parts: Vec<String> = struc.path.segments
-                                .iter()
-                                .map(|x| format!("{}", x.ident)).collect();
-                            Some((parts.join("::"), struc.path.to_token_stream()))
-                        },
-                        Pat::TupleStruct(tuple_struct) => {
-                            let parts: Vec<String> = tuple_struct.path.segments
-                                .iter()
-                                .map(|x| { format!("{}", x.ident) }).collect();
-                            Some((parts.join("::"), tuple_struct.path.to_token_stream()))
-                        },
-                        Pat::Wild(wild) => {
-                            Some((String::from("_"), wild.underscore_token.to_token_stream()))
+                .map(|arm| arm_key(&arm.pat, &order, &mut self.errors))
+                .collect();
+
+            match arm_keys {
+                // The order is meaningless without every pattern, only report the first unsupported one
+                Err(error) => self.errors.push(error),
+                Ok(arm_keys) => {
+                    // Catch-all arms are not part of the ordering, but unguarded ones must be last
+                    let last_index: usize = i.arms.len().saturating_sub(1);
+                    let mut sortable_keys: Vec<&ArmKey> = Vec::new();
+                    let mut sortable_patterns: Vec<&Pat> = Vec::new();
+                    let mut unguarded_keys: Vec<&ArmKey> = Vec::new();
+
+                    for (index, (arm, arm_key)) in i.arms.iter().zip(&arm_keys).enumerate() {
+                        if !arm_key.key.is_catch_all() {
+                            sortable_keys.push(arm_key);
+                            sortable_patterns.push(&arm.pat);
+                            if arm.guard.is_none() {
+                                unguarded_keys.push(arm_key);
+                            }
+                        } else if arm.guard.is_none() && index != last_index && !order.allow_wildcard_anywhere {
+                            self.errors.push(syn::Error::new_spanned(
+                                &arm_key.tokens, format!("catch-all pattern `{}` must be the last arm", arm_key)
+                            ));
                         }
-                        _ => {
-                            self.errors.push(
-                                syn::Error::new_spanned(x.pat.to_token_stream(), "unsupported by #[sorted]")
-                            );
-                            None
-                        },
                     }
-                }).collect();
 
-            let ident_str_vec: Vec<&String> = pattern_idents.iter().map(|(i, _ts)| i).collect();
-            let tokenstream_vec: Vec<&TokenStream> = pattern_idents.iter().map(|(_i, ts)| ts).collect();
+                    // Comparing last segments only makes sense if the paths share their prefix
+                    if !order.full_path {
+                        if let Some(error) = mixed_prefix_error(&sortable_keys) {
+                            self.errors.push(error);
+                        }
+                    }
 
-            // Determine if the expression is sorted
-            if !ident_str_vec.is_sorted() {
-                let (unsorted_index, should_be_index): (usize, usize) = find_unsorted_element(&ident_str_vec);
+                    let tokenstream_vec: Vec<&TokenStream> = sortable_keys.iter().map(|x| &x.tokens).collect();
 
-                let unsorted_element: &str = &ident_str_vec[unsorted_index];
-                let should_be_elmt: &str = &ident_str_vec[should_be_index];
-                let error_message: String = format!("{} should sort before {}", unsorted_element, should_be_elmt);
+                    // Determine if the expression is sorted, following the enum order if it has an id
+                    if let Some(id) = &order.id {
+                        match variants_check(id, warn, &sortable_patterns, &order) {
+                            Ok(variants_check) => self.variants_checks.push(variants_check),
+                            Err(error) => self.errors.push(error),
+                        }
+                    } else if let Some(error) = overlap_error(&unguarded_keys) {
+                        self.errors.push(error);
+                    } else if let Some(error) = unsorted_error(&sortable_keys, &tokenstream_vec, |a, b| a.key.compare(&b.key, &order), true) {
+                        self.errors.push(error);
+                    }
+                },
+            }
 
-                self.errors.push(syn::Error::new_spanned(tokenstream_vec[unsorted_index], error_message));
+            // Only the errors about the order are reported as warnings, invalid options are still errors
+            if warn {
+                self.warnings.extend(self.errors.drain(first_error..));
             }
 
             // Remove the attribute
@@ -85,4 +162,168 @@ impl VisitMut for CheckSortedMatch {
         // Continue the visit
         visit_mut::visit_expr_match_mut(self, i);
     }
+
+    fn visit_macro_mut(&mut self, i: &mut Macro) {
+        let mut attributes: Vec<TokenStream> = Vec::new();
+        let stripped_tokens: TokenStream = take_sorted_match_attributes(i.tokens.clone(), &mut attributes);
+        if attributes.is_empty() {
+            return
+        }
+
+        // The arguments of known macros are expressions, which can be checked like any other
+        let name: String = i.path.segments.last().map_or(String::new(), |x| format!("{}", x.ident));
+        if EXPRESSION_MACROS.contains(&name.as_str()) {
+            if let Ok(mut args) = i.parse_body::<MacroArgs>() {
+                for expr in &mut args.exprs {
+                    self.visit_expr_mut(expr);
+                }
+                i.tokens = args.to_token_stream();
+                return
+            }
+        }
+
+        // Otherwise the attributes would be left in, and rejected as attributes on expressions
+        for attribute in attributes {
+            self.errors.push(Error::new_spanned(
+                attribute, format!("#[sorted] can not be checked inside {}!, move the match out of the macro", name)
+            ));
+        }
+        i.tokens = stripped_tokens;
+    }
+}
+
+/// Remove the #[sorted] attributes applied to match expressions from the tokens, pushing them to the list.
+///
+/// Other attributes are kept, like #[sorted] on the items defined in a macro.
+fn take_sorted_match_attributes(tokens: TokenStream, attributes: &mut Vec<TokenStream>) -> TokenStream {
+    let trees: Vec<TokenTree> = tokens.into_iter().collect();
+    let mut result: Vec<TokenTree> = Vec::new();
+    let mut index: usize = 0;
+
+    let is_attribute = |index: usize| match (trees.get(index), trees.get(index + 1)) {
+        (Some(TokenTree::Punct(punct)), Some(TokenTree::Group(group))) => {
+            punct.as_char() == '#' && group.delimiter() == Delimiter::Bracket
+        },
+        _ => false,
+    };
+    let is_sorted = |group: &TokenTree| match group {
+        TokenTree::Group(group) => matches!(group.stream().into_iter().next(), Some(TokenTree::Ident(ident)) if ident == "sorted"),
+        _ => false,
+    };
+
+    while index < trees.len() {
+        if is_attribute(index) && is_sorted(&trees[index + 1]) {
+            // Look past the other attributes for the match keyword
+            let mut next: usize = index + 2;
+            while is_attribute(next) {
+                next += 2;
+            }
+
+            if matches!(trees.get(next), Some(TokenTree::Ident(ident)) if ident == "match") {
+                attributes.push(trees[index..index + 2].iter().cloned().collect());
+                index += 2;
+                continue
+            }
+        }
+
+        let tree: TokenTree = match &trees[index] {
+            TokenTree::Group(group) => {
+                let mut stripped: Group = Group::new(group.delimiter(), take_sorted_match_attributes(group.stream(), attributes));
+                stripped.set_span(group.span());
+                TokenTree::Group(stripped)
+            },
+            tree => tree.clone(),
+        };
+        result.push(tree);
+        index += 1;
+    }
+
+    result.into_iter().collect()
+}
+
+/// Return an error on the first qualified path whose prefix differs from the previous qualified paths.
+///
+/// Unqualified paths, like variants imported with `use Enum::*`, are compatible with any prefix.
+fn mixed_prefix_error(arm_keys: &[&ArmKey]) -> Option<Error> {
+    let mut first_prefix: Option<&str> = None;
+
+    for arm_key in arm_keys {
+        let keys: &[PatternKey] = match &arm_key.key {
+            PatternKey::Or(keys) => keys,
+            key => std::slice::from_ref(key),
+        };
+
+        for key in keys {
+            let PatternKey::Path(path, _) = key else { continue };
+            let Some((prefix, _)) = path.rsplit_once("::") else { continue };
+
+            match first_prefix {
+                None => first_prefix = Some(prefix),
+                Some(first) if first != prefix => return Some(Error::new_spanned(
+                    &arm_key.tokens,
+                    format!("arms mix different enum prefixes: {} and {}, use #[sorted(full_path)] to compare full paths", first, prefix),
+                )),
+                Some(_) => {},
+            }
+        }
+    }
+
+    None
+}
+
+/// Return an error on the first arm which can match the same value as a previous one, like a literal in a range.
+///
+/// Catch-all patterns, sorted last, and constants of unknown value do not count. Other overlapping arms can not be
+/// both sorted and reachable.
+fn overlap_error(arm_keys: &[&ArmKey]) -> Option<Error> {
+    arm_keys.iter().enumerate().find_map(|(index, arm_key)| {
+        let previous: &ArmKey = arm_keys[..index].iter().find(|x| x.key.overlaps(&arm_key.key, false))?;
+        Some(Error::new_spanned(
+            &arm_key.tokens, format!("{} overlaps {}, #[sorted] can not order overlapping arms", arm_key, previous)
+        ))
+    })
+}
+
+/// Build the invocation of the macro generated along with the enum with the id, passing the variant of each arm.
+///
+/// Every alternative of an or-pattern is passed as an arm of its own, so that each is checked against the enum.
+/// Return an error if an arm, or an alternative, does not name a variant.
+fn variants_check(id: &str, warn: bool, patterns: &[&Pat], order: &SortOrder) -> Result<TokenStream, Error> {
+    let mut alternatives: Vec<&Pat> = Vec::new();
+    for pat in patterns {
+        push_alternatives(pat, &mut alternatives);
+    }
+
+    // The patterns were already checked with the whole arms, their errors are not reported twice
+    let arm_keys: Vec<ArmKey> = alternatives
+        .iter()
+        .map(|pat| arm_key(pat, order, &mut Vec::new()))
+        .collect::<Result<_, _>>()?;
+
+    let arms: Vec<TokenStream> = arm_keys
+        .iter()
+        .map(|arm_key| match &arm_key.key {
+            PatternKey::Path(path, _) => {
+                let variant: Ident = Ident::new(path.rsplit("::").next().unwrap(), Span::call_site());
+                let tokens: &TokenStream = &arm_key.tokens;
+                Ok(quote! { #variant { #tokens } })
+            },
+            _ => Err(Error::new_spanned(&arm_key.tokens, format!("expected a variant of {}", id))),
+        })
+        .collect::<Result<_, _>>()?;
+
+    let macro_ident: Ident = format_ident!("__sorted_variants_{}", id);
+    Ok(quote! { #macro_ident! { #warn #(#arms),* } })
+}
+
+fn push_alternatives<'a>(pat: &'a Pat, alternatives: &mut Vec<&'a Pat>) {
+    match pat {
+        Pat::Or(pat_or) => {
+            for case in &pat_or.cases {
+                push_alternatives(case, alternatives);
+            }
+        },
+        Pat::Paren(paren) => push_alternatives(&paren.pat, alternatives),
+        _ => alternatives.push(pat),
+    }
 }
@@ -29,10 +29,7 @@
 use sorted::sorted;
 
 #[sorted]
-pub struct Error {
-    kind: ErrorKind,
-    message: String,
-}
+pub struct Error(ErrorKind, String);
 
 enum ErrorKind {
     Io,
@@ -0,0 +1,34 @@
+// Every out-of-order element should be reported in a single compilation, both
+// for enums and for each sorted match expression of a checked function.
+
+use sorted::sorted;
+
+#[sorted]
+pub enum Error {
+    ThatFailed,
+    ThisFailed,
+    SomethingFailed,
+    WhoKnowsWhatFailed,
+    AnotherFailed,
+}
+
+#[sorted::check]
+fn f(error: &Error) -> &str {
+    use self::Error::*;
+
+    let first = #[sorted] match error {
+        ThisFailed => "this",
+        ThatFailed => "that",
+        _ => "other",
+    };
+
+    let second = #[sorted] match error {
+        WhoKnowsWhatFailed => "who knows",
+        SomethingFailed => "something",
+        _ => "other",
+    };
+
+    if first.is_empty() { second } else { first }
+}
+
+fn main() {}
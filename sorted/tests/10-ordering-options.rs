@@ -0,0 +1,60 @@
+// The comparison used by #[sorted] can be configured with arguments, on enums
+// as well as on match expressions.
+
+use sorted::sorted;
+
+#[sorted(natural)]
+pub enum Interrupt {
+    Irq1,
+    Irq2,
+    Irq10,
+    Irq11,
+}
+
+#[sorted(case_insensitive)]
+#[allow(non_camel_case_types)]
+pub enum Fruit {
+    apple,
+    Banana,
+    cherry,
+    ZERO,
+}
+
+#[sorted(reverse)]
+pub enum Level {
+    Warning,
+    Info,
+    Error,
+    Debug,
+}
+
+#[sorted(by_discriminant)]
+pub enum Status {
+    Zulu = -1,
+    Alpha,
+    Yankee = 4,
+    Bravo = 10,
+}
+
+#[sorted(natural, case_insensitive)]
+#[allow(non_camel_case_types)]
+pub enum Port {
+    eth2,
+    Eth10,
+}
+
+#[sorted::check]
+fn is_shared(interrupt: &Interrupt) -> bool {
+    use self::Interrupt::*;
+
+    #[sorted(natural)]
+    match interrupt {
+        Irq2 => true,
+        Irq10 => true,
+        _ => false,
+    }
+}
+
+fn main() {
+    let _ = is_shared(&Interrupt::Irq1);
+}
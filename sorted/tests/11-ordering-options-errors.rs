@@ -0,0 +1,37 @@
+// Violations are reported according to the configured ordering, and unknown
+// options are rejected.
+
+use sorted::sorted;
+
+#[sorted(natural)]
+pub enum Interrupt {
+    Irq2,
+    Irq10,
+    Irq1,
+}
+
+#[sorted(by_discriminant)]
+pub enum Status {
+    Alpha = 2,
+    Bravo = 1,
+}
+
+#[sorted(by_discriminant)]
+pub enum Computed {
+    Alpha = 1 << 2,
+}
+
+#[sorted(alphabetical)]
+pub enum Unknown {
+    Alpha,
+}
+
+#[sorted::check]
+fn f(level: u8) -> u8 {
+    #[sorted(by_discriminant)]
+    match level {
+        _ => 0,
+    }
+}
+
+fn main() {}
@@ -0,0 +1,49 @@
+// Besides enums, #[sorted] checks the order of struct fields, impl block
+// items, inline module items, braced use lists and string arrays.
+
+use sorted::sorted;
+
+#[sorted]
+use std::fmt::{self, Debug, Display};
+
+#[sorted]
+pub struct Config {
+    depth: usize,
+    name: String,
+    verbose: bool,
+}
+
+#[sorted]
+impl Config {
+    const DEFAULT_DEPTH: usize = 4;
+
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+#[sorted(case_insensitive)]
+mod handlers {
+    pub fn close() {}
+
+    pub struct Handler;
+
+    pub fn open() {}
+}
+
+#[sorted]
+const KEYWORDS: &[&str] = &["as", "break", "const", "continue"];
+
+#[sorted(natural)]
+const LEVELS: [&str; 3] = ["level2", "level10", "level11"];
+
+fn main() {
+    let _: &dyn Debug = &KEYWORDS;
+    let _: &dyn Display = &LEVELS[0];
+    let _ = fmt::Error;
+    let _ = handlers::Handler;
+}
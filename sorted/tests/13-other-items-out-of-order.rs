@@ -0,0 +1,42 @@
+// Out-of-order struct fields, impl block items, module items, use lists and
+// string arrays are reported like enum variants.
+
+use sorted::sorted;
+
+#[sorted]
+use std::{io, fmt::{Write, Debug}};
+
+#[sorted]
+pub struct Config {
+    name: String,
+    depth: usize,
+}
+
+#[sorted]
+impl Config {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+}
+
+#[sorted]
+mod handlers {
+    pub fn open() {}
+
+    pub fn close() {}
+}
+
+#[sorted]
+const KEYWORDS: &[&str] = &["break", "as"];
+
+#[sorted]
+const NOT_STRINGS: &[u8] = &[1, 2];
+
+#[sorted]
+use std::collections::HashMap;
+
+fn main() {}
@@ -0,0 +1,25 @@
+// With #[sorted(groups)], a #[sorted::section] marker on a variant starts a new
+// group, and variants are only required to be sorted within their group.
+
+use sorted::sorted;
+
+#[sorted(groups)]
+pub enum Error {
+    Config,
+    Internal,
+    #[sorted::section("network errors")]
+    Dns,
+    Timeout,
+    /// The marker can be placed next to other attributes.
+    #[sorted::section("disk errors")]
+    #[allow(dead_code)]
+    Full,
+    NotFound,
+    ReadOnly,
+    #[sorted::section]
+    Aborted,
+}
+
+fn main() {
+    let _ = Error::Aborted;
+}
@@ -0,0 +1,25 @@
+// Diagnostics name the group in which a variant is out of order, and section
+// markers require grouping to be enabled.
+
+use sorted::sorted;
+
+#[sorted(groups)]
+pub enum Error {
+    Internal,
+    Config,
+    #[sorted::section("network errors")]
+    Timeout,
+    Dns,
+    #[sorted::section("disk errors")]
+    Full,
+    NotFound,
+}
+
+#[sorted]
+pub enum Ungrouped {
+    Config,
+    #[sorted::section]
+    Aborted,
+}
+
+fn main() {}
@@ -0,0 +1,63 @@
+// #[sorted::check] is not limited to free functions: it checks the sorted match
+// expressions of every function, method, closure and constant expression
+// nested in the item it is attached to.
+
+use sorted::sorted;
+
+#[sorted]
+pub enum Conference {
+    RustBeltRust,
+    RustConf,
+    RustFest,
+}
+
+#[sorted::check]
+impl Conference {
+    pub fn region(&self) -> &str {
+        #[sorted]
+        match self {
+            Conference::RustFest => "Europe",
+            _ => "elsewhere",
+        }
+    }
+}
+
+#[sorted::check]
+pub trait Named {
+    fn conference(&self) -> &Conference;
+
+    fn name(&self) -> &str {
+        let name = |conference: &Conference| #[sorted] match conference {
+            Conference::RustBeltRust => "Rust Belt Rust",
+            Conference::RustConf => "RustConf",
+            Conference::RustFest => "RustFest",
+        };
+        name(self.conference())
+    }
+}
+
+#[sorted::check]
+mod schedule {
+    use super::Conference;
+
+    pub fn month(conference: &Conference) -> u8 {
+        #[sorted]
+        match conference {
+            Conference::RustBeltRust => 10,
+            Conference::RustConf => 9,
+            Conference::RustFest => 11,
+        }
+    }
+}
+
+#[sorted::check]
+const DEFAULT_MONTH: u8 = #[sorted] match Some(9) {
+    None => 1,
+    Some(month) => month,
+};
+
+fn main() {
+    let _ = Conference::RustConf.region();
+    let _ = schedule::month(&Conference::RustConf);
+    let _ = DEFAULT_MONTH;
+}
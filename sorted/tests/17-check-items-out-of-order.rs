@@ -0,0 +1,39 @@
+// Every unsorted match nested in a checked item is reported.
+
+use sorted::sorted;
+
+#[sorted]
+pub enum Conference {
+    RustBeltRust,
+    RustConf,
+    RustFest,
+}
+
+#[sorted::check]
+mod schedule {
+    use super::Conference;
+
+    pub fn month(conference: &Conference) -> u8 {
+        #[sorted]
+        match conference {
+            Conference::RustConf => 9,
+            Conference::RustBeltRust => 10,
+            Conference::RustFest => 11,
+        }
+    }
+
+    pub struct Calendar;
+
+    impl Calendar {
+        pub fn city(&self, conference: &Conference) -> &str {
+            #[sorted]
+            match conference {
+                Conference::RustFest => "Barcelona",
+                Conference::RustConf => "Portland",
+                _ => "Pittsburgh",
+            }
+        }
+    }
+}
+
+fn main() {}
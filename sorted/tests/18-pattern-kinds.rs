@@ -0,0 +1,68 @@
+// Besides paths, sorted matches support or-patterns, literals, ranges, nested
+// patterns and guards. Or-patterns are ranked by their first alternative,
+// numbers are compared by value and ranges by their start, with the MIN and
+// MAX constants taken as their value and other constants ranked first.
+
+#[derive(Clone, Copy)]
+pub enum Kind {
+    Ack,
+    Data,
+    Nack,
+}
+
+const LIMIT: i64 = -1000;
+
+#[sorted::check]
+fn decode(kind: Option<Kind>, code: u8, delta: i64, tag: char, magic: &[u8], name: &str) -> u32 {
+    let by_kind = #[sorted]
+    match kind {
+        None => 3,
+        Some(Kind::Ack) | Some(Kind::Nack) => 1,
+        Some(Kind::Data) => 2,
+    };
+
+    let by_code = #[sorted]
+    match code {
+        0 => 0,
+        1..=9 if code % 2 == 0 => 1,
+        1..=9 => 2,
+        10 | 20 | 100 => 3,
+        0x7f..=0xff => 4,
+        _ => 5,
+    };
+
+    let by_delta = #[sorted]
+    match delta {
+        ..=LIMIT => 0,
+        i64::MIN..=-1 => 1,
+        0 => 2,
+        1..=i64::MAX => 3,
+    };
+
+    let by_tag = #[sorted]
+    match tag {
+        'A'..='Z' => 0,
+        'x' => 1,
+        other => other as u32,
+    };
+
+    let by_magic = #[sorted]
+    match magic {
+        b"ELF" => 0,
+        b"MZ" => 1,
+        _ => 2,
+    };
+
+    let by_name = #[sorted(case_insensitive)]
+    match name {
+        "alpha" => 0,
+        "Beta" => 1,
+        _ => 2,
+    };
+
+    by_kind + by_code + by_delta + by_tag + by_magic + by_name
+}
+
+fn main() {
+    let _ = decode(Some(Kind::Data), 3, -4, 'q', b"MZ", "Beta");
+}
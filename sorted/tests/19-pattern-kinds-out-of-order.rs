@@ -0,0 +1,60 @@
+// Out-of-order alternatives, literals, ranges and nested patterns are reported,
+// as are arms overlapping a range, which can not be both sorted and reachable.
+
+pub enum Kind {
+    Ack,
+    Data,
+    Nack,
+}
+
+#[sorted::check]
+fn decode(kind: Option<Kind>, code: i32, name: &str) -> u32 {
+    let by_kind = #[sorted]
+    match kind {
+        Some(Kind::Nack) | Some(Kind::Ack) => 1,
+        Some(Kind::Data) => 2,
+        None => 3,
+    };
+
+    let by_code = #[sorted]
+    match code {
+        10 => 0,
+        -1 => 1,
+        2..=9 => 2,
+        1 => 3,
+        _ => 4,
+    };
+
+    let by_sign = #[sorted]
+    match code {
+        1..=i32::MAX => 0,
+        i32::MIN..=-1 => 1,
+        _ => 2,
+    };
+
+    let by_overlap = #[sorted]
+    match code {
+        5 => 0,
+        1..=9 => 1,
+        _ => 2,
+    };
+
+    let by_name = #[sorted]
+    match name {
+        "beta" => 0,
+        "alpha" => 1,
+        _ => 2,
+    };
+
+    let by_nested = #[sorted]
+    match kind {
+        None => 0,
+        Some(Kind::Data) => 1,
+        Some(Kind::Ack) => 2,
+        Some(_) => 3,
+    };
+
+    by_kind + by_code + by_sign + by_overlap + by_name + by_nested
+}
+
+fn main() {}
@@ -0,0 +1,36 @@
+// Catch-all arms, the wildcard and bare bindings, are not part of the ordering.
+// Unguarded ones must be the last arm, unless allow_wildcard_anywhere is set.
+// A match without arms, on an uninhabited type, is trivially sorted.
+
+pub enum Never {}
+
+#[sorted::check]
+#[allow(unreachable_patterns)]
+fn describe(code: u8, name: &str) -> String {
+    let by_code = #[sorted]
+    match code {
+        0 => "zero",
+        small if small < 10 => "small",
+        1..=99 => "medium",
+        other => return format!("large {}", other),
+    };
+
+    let by_name = #[sorted(allow_wildcard_anywhere)]
+    match name {
+        "alpha" => "first",
+        _ => "other",
+        "beta" => "second",
+    };
+
+    format!("{} {}", by_code, by_name)
+}
+
+#[sorted::check]
+pub fn unreachable(never: Never) -> u8 {
+    #[sorted]
+    match never {}
+}
+
+fn main() {
+    let _ = describe(42, "beta");
+}
@@ -0,0 +1,23 @@
+// A catch-all arm in the middle of a sorted match hides the arms after it, it
+// must be the last one.
+
+#[sorted::check]
+fn describe(code: u8, name: &str) -> u8 {
+    let by_code = #[sorted]
+    match code {
+        0 => 0,
+        other => other,
+        1..=99 => 1,
+    };
+
+    let by_name = #[sorted]
+    match name {
+        _ => 0,
+        "beta" => 2,
+        "alpha" => 1,
+    };
+
+    by_code + by_name
+}
+
+fn main() {}
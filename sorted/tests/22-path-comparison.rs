@@ -0,0 +1,50 @@
+// Qualified patterns are compared by their last segment, so that arms naming
+// a variant through its enum sort along with variants imported by `use`.
+//
+// With #[sorted(full_path)], the joined paths are compared instead, which lets
+// arms from different enums live in the same match.
+
+mod error {
+    pub enum Error {
+        Fmt,
+        Io,
+        Utf8,
+    }
+
+    pub mod fallback {
+        pub enum Error {
+            Utf8,
+        }
+    }
+}
+
+use error::{fallback, Error};
+use error::Error::Fmt;
+
+pub enum Any {
+    Error(Error),
+    Fallback(fallback::Error),
+}
+
+#[sorted::check]
+fn describe(error: Error, any: Any) -> (&'static str, &'static str) {
+    let short = #[sorted]
+    match error {
+        Fmt => "fmt",
+        Error::Io => "io",
+        Error::Utf8 => "utf8",
+    };
+
+    let long = #[sorted(full_path)]
+    match any {
+        Any::Error(Error::Fmt) => "fmt",
+        Any::Error(_) => "other",
+        Any::Fallback(fallback::Error::Utf8) => "utf8",
+    };
+
+    (short, long)
+}
+
+fn main() {
+    let _ = describe(Error::Io, Any::Fallback(fallback::Error::Utf8));
+}
@@ -0,0 +1,21 @@
+// Comparing the last segment of arms reached through different paths would be
+// meaningless, so mixing prefixes is an error unless #[sorted(full_path)] is
+// used.
+
+enum Error {
+    Fmt,
+    Io,
+}
+
+use Error as Kind;
+
+#[sorted::check]
+fn describe(error: Error) -> &'static str {
+    #[sorted]
+    match error {
+        Error::Fmt => "fmt",
+        Kind::Io => "io",
+    }
+}
+
+fn main() {}
@@ -0,0 +1,45 @@
+// An enum and the matches over it can share an id, so that the matches list
+// the variants in the order the enum declares them, rather than alphabetically.
+// This is useful when the enum is grouped or sorted by discriminant.
+//
+// The enum generates a hidden macro carrying its variants, which the matches
+// consult. Like any `macro_rules!`, the enum must come before the matches.
+
+#[sorted::sorted(groups, id = "ErrorKind")]
+pub enum ErrorKind {
+    #[sorted::section("io")]
+    NotFound,
+    PermissionDenied,
+    #[sorted::section("parsing")]
+    Eof,
+    InvalidData,
+}
+
+#[sorted::check]
+fn describe(kind: ErrorKind) -> &'static str {
+    #[sorted(id = "ErrorKind")]
+    match kind {
+        ErrorKind::NotFound => "not found",
+        ErrorKind::PermissionDenied => "permission denied",
+        ErrorKind::Eof | ErrorKind::InvalidData => "invalid data",
+    }
+}
+
+mod errors {
+    use crate::ErrorKind;
+
+    #[sorted::check]
+    pub fn is_io(kind: &ErrorKind) -> bool {
+        #[sorted(id = "ErrorKind")]
+        match kind {
+            ErrorKind::NotFound => true,
+            ErrorKind::PermissionDenied => true,
+            _ => false,
+        }
+    }
+}
+
+fn main() {
+    assert_eq!(describe(ErrorKind::Eof), "invalid data");
+    assert!(errors::is_io(&ErrorKind::NotFound));
+}
@@ -0,0 +1,51 @@
+// Matches sharing the id of an enum must list its variants in the declaration
+// order, and only its variants. Every alternative of an or-pattern is checked,
+// and alternatives follow the declaration order too, rather than the alphabet.
+
+#[sorted::sorted(by_discriminant, id = "Status")]
+pub enum Status {
+    Ok = 200,
+    NotFound = 404,
+    Internal = 500,
+}
+
+#[sorted::check]
+fn describe(status: Status) -> &'static str {
+    #[sorted(id = "Status")]
+    match status {
+        Status::Ok => "ok",
+        Status::Internal => "internal",
+        Status::NotFound => "not found",
+    }
+}
+
+#[sorted::check]
+fn code(status: Status) -> u16 {
+    #[sorted(id = "Status")]
+    match status {
+        Status::Ok => 200,
+        Status::Missing => 404,
+        _ => 500,
+    }
+}
+
+#[sorted::check]
+fn is_success(status: Status) -> bool {
+    #[sorted(id = "Status")]
+    match status {
+        Status::Ok | Status::NotFound => true,
+        Status::Internal => false,
+    }
+}
+
+#[sorted::check]
+fn is_error(status: Status) -> bool {
+    #[sorted(id = "Status")]
+    match status {
+        Status::Ok => false,
+        Status::NotFound | Status::Unavailable => true,
+        _ => true,
+    }
+}
+
+fn main() {}
@@ -0,0 +1,43 @@
+// Long lists which are not items can be checked too.
+//
+// The check_list! macro checks comma-separated elements and emits them
+// unchanged. Given a single macro invocation, it checks the elements of its
+// body, which suits dispatch tables built with `macro_rules!`.
+//
+// The #[sorted(derives)] attribute checks the derive list of an item instead
+// of its content, comparing the last segment of each path.
+
+macro_rules! dispatch {
+    ($($name:literal => $handler:ident),* $(,)?) => {
+        fn dispatch(name: &str) -> Option<u8> {
+            match name {
+                $($name => Some($handler()),)*
+                _ => None,
+            }
+        }
+    };
+}
+
+fn close() -> u8 { 0 }
+fn open() -> u8 { 1 }
+fn read() -> u8 { 2 }
+
+sorted::check_list! {
+    dispatch! {
+        "close" => close,
+        "open" => open,
+        "read" => read,
+    }
+}
+
+#[sorted::sorted(derives)]
+#[derive(Clone, Copy, Debug, Default, std::hash::Hash, PartialEq)]
+pub struct Flags {
+    write: bool,
+    read: bool,
+}
+
+fn main() {
+    assert_eq!(dispatch("open"), Some(1));
+    assert_eq!(Flags::default(), Flags { write: false, read: false });
+}
@@ -0,0 +1,18 @@
+// Lists checked by check_list! and #[sorted(derives)] report their unsorted
+// elements like any other sorted list.
+
+macro_rules! names {
+    ($($name:ident),*) => {};
+}
+
+sorted::check_list! {
+    names!(alpha, gamma, beta);
+}
+
+#[sorted::sorted(derives)]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Flags {
+    read: bool,
+}
+
+fn main() {}
@@ -0,0 +1,89 @@
+// During a migration, #[sorted(warn)] reports unsorted elements as warnings
+// rather than failing the build. Setting SORTED_LEVEL=warn in the environment
+// of the build makes it the default for every #[sorted] attribute.
+//
+// Proc macros can not emit warnings on stable, so the warnings are deprecation
+// warnings pointing at the unsorted elements.
+//
+// The warnings stay valid on associated items, where only named items are
+// allowed next to the annotated one: they are reported from inside the body of
+// a method or the initializer of a constant. A type named `Option` in scope
+// does not get in the way of the expansions.
+
+#![allow(dead_code)]
+
+pub struct Option;
+
+#[sorted::sorted(warn)]
+pub enum Conference {
+    RustBelt,
+    RustConf,
+    EuroRust,
+}
+
+#[sorted::check]
+fn describe(conference: Conference) -> &'static str {
+    #[sorted(warn)]
+    match conference {
+        Conference::RustConf => "Portland",
+        Conference::RustBelt => "Columbus",
+        Conference::EuroRust => "Vienna",
+    }
+}
+
+macro_rules! getters {
+    ($($name:ident),* $(,)?) => {
+        $(fn $name(&self) -> u8 { 0 })*
+    };
+}
+
+pub struct Registry;
+
+impl Registry {
+    #[sorted::sorted]
+    const NAMES: &'static [&'static str] = &["alpha", "beta", "gamma"];
+
+    #[sorted::sorted(warn)]
+    const LEGACY: &'static [&'static str] = &["zeta", "alpha"];
+
+    #[sorted::check]
+    const CODE: u8 = #[sorted]
+    match 1 {
+        0 => 0,
+        1 => 1,
+        _ => 2,
+    };
+
+    #[sorted::check]
+    fn describe(&self, code: u8) -> &'static str {
+        #[sorted(warn)]
+        match code {
+            1 => "one",
+            0 => "zero",
+            _ => "many",
+        }
+    }
+
+    sorted::check_list! {
+        getters! { first, second, third }
+    }
+}
+
+pub trait Describe {
+    #[sorted::check]
+    fn describe(&self, code: u8) -> &'static str {
+        #[sorted]
+        match code {
+            0 => "zero",
+            _ => "many",
+        }
+    }
+}
+
+fn main() {
+    assert_eq!(describe(Conference::EuroRust), "Vienna");
+    assert_eq!(Registry::NAMES.len(), 3);
+    assert_eq!(Registry::CODE, 1);
+    assert_eq!(Registry.describe(1), "one");
+    assert_eq!(Registry.second(), 0);
+}
@@ -0,0 +1,13 @@
+// The warnings of #[sorted(warn)] are deprecation warnings, so they follow the
+// level of the `deprecated` lint like any other warning.
+
+#![deny(deprecated)]
+
+#[sorted::sorted(warn)]
+pub enum Conference {
+    RustBelt,
+    RustConf,
+    EuroRust,
+}
+
+fn main() {}
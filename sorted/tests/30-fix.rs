@@ -0,0 +1,32 @@
+// For enums whose declaration order has no meaning, #[sorted(fix)] sorts the
+// variants rather than reporting them. Attributes, docs and fields move along
+// with their variant, and each section is sorted independently.
+
+#[sorted::sorted(fix)]
+#[derive(Debug, PartialEq, PartialOrd)]
+pub enum Shape {
+    /// A square, by the length of its side.
+    Square(f64),
+    Circle { radius: f64 },
+    #[allow(dead_code)]
+    Point,
+}
+
+#[sorted::sorted(fix, groups)]
+#[derive(Debug, PartialEq, PartialOrd)]
+pub enum Token {
+    #[sorted::section("keywords")]
+    While,
+    If,
+    #[sorted::section("punctuation")]
+    Semi,
+    Comma,
+}
+
+fn main() {
+    // Derived comparisons follow the declaration order, which is now sorted
+    assert!(Shape::Circle { radius: 1.0 } < Shape::Square(1.0));
+    assert!(Token::If < Token::While);
+    assert!(Token::While < Token::Comma);
+    assert!(Token::Comma < Token::Semi);
+}
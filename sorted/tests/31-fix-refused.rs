@@ -0,0 +1,18 @@
+// Reordering variants changes their discriminants, so #[sorted(fix)] refuses
+// enums which expose them through explicit discriminants or a #[repr].
+
+#[sorted::sorted(fix)]
+pub enum Status {
+    Ok = 200,
+    NotFound = 404,
+    Internal = 500,
+}
+
+#[sorted::sorted(fix)]
+#[repr(u8)]
+pub enum Opcode {
+    Push,
+    Pop,
+}
+
+fn main() {}
@@ -0,0 +1,25 @@
+// With #[sorted(meta)], the enum gets methods to look its variants up by name:
+// the sorted list of names, a binary search from a name to its variant, and the
+// name and index of a variant in that list.
+
+#[sorted::sorted(meta)]
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    Add,
+    Commit,
+    Push,
+    Status,
+}
+
+#[sorted::sorted(meta)]
+pub enum Never {}
+
+fn main() {
+    assert_eq!(Command::VARIANT_NAMES, ["Add", "Commit", "Push", "Status"]);
+    assert_eq!(Command::from_name("Push"), Some(Command::Push));
+    assert_eq!(Command::from_name("Pull"), None);
+    assert_eq!(Command::Commit.name(), "Commit");
+    assert_eq!(Command::Status.ordinal(), 3);
+    assert!(Never::VARIANT_NAMES.is_empty());
+    assert!(Never::from_name("Add").is_none());
+}
@@ -0,0 +1,23 @@
+// The lookup methods of #[sorted(meta)] build variants from their name, so
+// every variant must be a unit variant. The names are searched in byte order,
+// so the other orderings are rejected.
+
+#[sorted::sorted(meta)]
+pub enum Shape {
+    Circle(f64),
+    Point,
+}
+
+#[sorted::sorted(meta, natural)]
+pub enum Irq {
+    Irq2,
+    Irq10,
+}
+
+#[sorted::sorted(meta)]
+pub struct Point {
+    x: f64,
+    y: f64,
+}
+
+fn main() {}
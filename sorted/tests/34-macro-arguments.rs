@@ -0,0 +1,43 @@
+// Sorted matches may be written in the arguments of standard macros taking
+// expressions, like `vec!` or `println!`, which are parsed and checked like
+// any other expression.
+
+use std::fmt::{self, Display};
+
+pub enum Level {
+    Debug,
+    Error,
+    Info,
+}
+
+impl Display for Level {
+    #[sorted::check]
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "[{}]", #[sorted] match self {
+            Level::Debug => "debug",
+            Level::Error => "error",
+            Level::Info => "info",
+        })
+    }
+}
+
+#[sorted::check]
+fn codes(level: Level) -> Vec<u8> {
+    let repeated = vec![#[sorted] match level {
+        Level::Debug => 0,
+        Level::Error => 2,
+        Level::Info => 1,
+    }; 3];
+
+    assert_eq!(repeated.len(), 3, "{}", #[sorted] match repeated[0] {
+        0 => "zero",
+        _ => "other",
+    });
+
+    repeated
+}
+
+fn main() {
+    assert_eq!(Level::Error.to_string(), "[error]");
+    assert_eq!(codes(Level::Info), [1, 1, 1]);
+}
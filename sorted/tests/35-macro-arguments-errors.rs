@@ -0,0 +1,24 @@
+// Sorted matches in the arguments of standard macros are checked, while the
+// ones in other macros can not be reached and are reported as such.
+
+macro_rules! twice {
+    ($e:expr) => {
+        ($e, $e)
+    };
+}
+
+#[sorted::check]
+fn describe(code: u8) -> String {
+    let pair = twice!(#[sorted] match code {
+        0 => "zero",
+        _ => "other",
+    });
+
+    format!("{:?} {}", pair, #[sorted] match code {
+        1 => "one",
+        0 => "zero",
+        _ => "other",
+    })
+}
+
+fn main() {}
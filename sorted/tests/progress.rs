@@ -9,4 +9,31 @@ fn tests() {
     t.compile_fail("tests/06-pattern-path.rs");
     t.compile_fail("tests/07-unrecognized-pattern.rs");
     t.pass("tests/08-underscore.rs");
+    t.compile_fail("tests/09-all-out-of-order.rs");
+    t.pass("tests/10-ordering-options.rs");
+    t.compile_fail("tests/11-ordering-options-errors.rs");
+    t.pass("tests/12-other-items.rs");
+    t.compile_fail("tests/13-other-items-out-of-order.rs");
+    t.pass("tests/14-groups.rs");
+    t.compile_fail("tests/15-groups-out-of-order.rs");
+    t.pass("tests/16-check-items.rs");
+    t.compile_fail("tests/17-check-items-out-of-order.rs");
+    t.pass("tests/18-pattern-kinds.rs");
+    t.compile_fail("tests/19-pattern-kinds-out-of-order.rs");
+    t.pass("tests/20-catch-all.rs");
+    t.compile_fail("tests/21-catch-all-not-last.rs");
+    t.pass("tests/22-path-comparison.rs");
+    t.compile_fail("tests/23-mixed-prefixes.rs");
+    t.pass("tests/24-enum-id.rs");
+    t.compile_fail("tests/25-enum-id-mismatch.rs");
+    t.pass("tests/26-lists.rs");
+    t.compile_fail("tests/27-lists-out-of-order.rs");
+    t.pass("tests/28-warn.rs");
+    t.compile_fail("tests/29-warn-denied.rs");
+    t.pass("tests/30-fix.rs");
+    t.compile_fail("tests/31-fix-refused.rs");
+    t.pass("tests/32-meta.rs");
+    t.compile_fail("tests/33-meta-errors.rs");
+    t.pass("tests/34-macro-arguments.rs");
+    t.compile_fail("tests/35-macro-arguments-errors.rs");
 }